@@ -0,0 +1,244 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// Tamaño de los bloques en que se envían los cuerpos en streaming
+pub const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Lector usado por los cuerpos en streaming
+pub type BodyReader = Box<dyn Read + Send>;
+
+/// Productor de bloques usado por los cuerpos generados
+pub type BodyIter = Box<dyn Iterator<Item = Vec<u8>> + Send>;
+
+/// Cuerpo de una respuesta HTTP: bytes en memoria, un archivo, un lector (pipe, etc.)
+/// enviado por bloques o un iterador que produce el contenido a medida que se escribe
+pub enum Body {
+    Bytes(Vec<u8>),
+    /// Archivo leído desde su posición actual, con la cantidad de bytes a enviar.
+    /// Se mantiene aparte de `Reader` para poder enviarlo con `sendfile(2)`
+    File(RefCell<File>, u64),
+    /// Lector con su largo, si se conoce de antemano
+    Reader(RefCell<BodyReader>, Option<u64>),
+    Iter(RefCell<BodyIter>),
+}
+
+impl Body {
+    /// Cuerpo vacío
+    pub fn empty() -> Self {
+        Body::Bytes(Vec::new())
+    }
+
+    /// Cuerpo leído en streaming desde `reader`
+    pub fn reader<R: Read + Send + 'static>(reader: R, len: Option<u64>) -> Self {
+        // Con largo conocido no se envían más bytes de los anunciados
+        let reader: BodyReader = match len {
+            Some(len) => Box::new(reader.take(len)),
+            None => Box::new(reader),
+        };
+        Body::Reader(RefCell::new(reader), len)
+    }
+
+    /// Cuerpo con el contenido de un archivo ya abierto, sin cargarlo en memoria
+    pub fn file(file: File) -> io::Result<Self> {
+        let len = file.metadata()?.len();
+        Ok(Body::File(RefCell::new(file), len))
+    }
+
+    /// Cuerpo con `len` bytes de un archivo a partir de `start`
+    pub fn file_part(mut file: File, start: u64, len: u64) -> io::Result<Self> {
+        file.seek(SeekFrom::Start(start))?;
+        Ok(Body::File(RefCell::new(file), len))
+    }
+
+    /// Cuerpo producido por un iterador de bloques; su largo no se conoce de antemano
+    pub fn iter<I>(iter: I) -> Self
+    where
+        I: Iterator<Item = Vec<u8>> + Send + 'static,
+    {
+        Body::Iter(RefCell::new(Box::new(iter)))
+    }
+
+    /// Largo del cuerpo en bytes, si se conoce antes de enviarlo
+    pub fn len(&self) -> Option<u64> {
+        match self {
+            Body::Bytes(bytes) => Some(bytes.len() as u64),
+            Body::File(_, len) => Some(*len),
+            Body::Reader(_, len) => *len,
+            Body::Iter(_) => None,
+        }
+    }
+
+    /// Indica si el cuerpo está vacío; los cuerpos de largo desconocido no lo están
+    pub fn is_empty(&self) -> bool {
+        self.len() == Some(0)
+    }
+
+    /// Contenido en memoria, o None si el cuerpo es un stream
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Body::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// Consume el cuerpo y devuelve todo su contenido en memoria
+    pub fn into_bytes(self) -> io::Result<Vec<u8>> {
+        match self {
+            Body::Bytes(bytes) => Ok(bytes),
+            body => {
+                let mut buf = Vec::new();
+                body.write_chunks(&mut |chunk| buf.write_all(chunk))?;
+                Ok(buf)
+            }
+        }
+    }
+
+    /// Recorre el cuerpo en bloques de a lo sumo `CHUNK_SIZE` bytes.
+    /// Los cuerpos en streaming se agotan: una segunda llamada no produce nada
+    pub fn write_chunks<F>(&self, f: &mut F) -> io::Result<()>
+    where
+        F: FnMut(&[u8]) -> io::Result<()>,
+    {
+        match self {
+            Body::Bytes(bytes) => bytes.chunks(CHUNK_SIZE).try_for_each(f),
+            Body::File(file, len) => {
+                let mut file = file.borrow_mut();
+                read_chunks(&mut (&mut *file).take(*len), f)
+            }
+            Body::Reader(reader, _) => read_chunks(&mut *reader.borrow_mut(), f),
+            Body::Iter(iter) => {
+                let mut iter = iter.borrow_mut();
+                for chunk in iter.by_ref() {
+                    for piece in chunk.chunks(CHUNK_SIZE) {
+                        f(piece)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// Lee `reader` hasta agotarlo, pasando cada bloque a `f`
+fn read_chunks<R, F>(reader: &mut R, f: &mut F) -> io::Result<()>
+where
+    R: Read + ?Sized,
+    F: FnMut(&[u8]) -> io::Result<()>,
+{
+    let mut buf = vec![0; CHUNK_SIZE];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => f(&buf[..n])?,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+impl Default for Body {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl fmt::Debug for Body {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Body::Bytes(bytes) => write!(f, "Bytes({:?})", String::from_utf8_lossy(bytes)),
+            Body::File(_, len) => write!(f, "File({})", len),
+            Body::Reader(_, len) => write!(f, "Reader({:?})", len),
+            Body::Iter(_) => write!(f, "Iter"),
+        }
+    }
+}
+
+impl From<Vec<u8>> for Body {
+    fn from(bytes: Vec<u8>) -> Self {
+        Body::Bytes(bytes)
+    }
+}
+
+impl From<&[u8]> for Body {
+    fn from(bytes: &[u8]) -> Self {
+        Body::Bytes(bytes.to_vec())
+    }
+}
+
+impl From<String> for Body {
+    fn from(s: String) -> Self {
+        Body::Bytes(s.into_bytes())
+    }
+}
+
+impl From<&str> for Body {
+    fn from(s: &str) -> Self {
+        Body::Bytes(s.as_bytes().to_vec())
+    }
+}
+
+/// Compara el contenido en memoria; un cuerpo en streaming nunca es igual
+impl<T: AsRef<[u8]> + ?Sized> PartialEq<T> for Body {
+    fn eq(&self, other: &T) -> bool {
+        self.as_bytes() == Some(other.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn collect(body: &Body) -> Vec<Vec<u8>> {
+        let mut chunks = Vec::new();
+        body.write_chunks(&mut |chunk| {
+            chunks.push(chunk.to_vec());
+            Ok(())
+        })
+        .unwrap();
+        chunks
+    }
+
+    #[test]
+    // prueba de cuerpos en memoria
+    fn test_bytes_body() {
+        let body = Body::from("hola");
+        assert_eq!(body.len(), Some(4));
+        assert_eq!(body, "hola");
+        assert_eq!(body, b"hola");
+        assert!(Body::empty().is_empty());
+        assert_eq!(collect(&body), vec![b"hola".to_vec()]);
+    }
+
+    #[test]
+    // prueba de lectura por bloques de tamaño fijo respetando el largo anunciado
+    fn test_reader_body() {
+        let data = vec![7u8; CHUNK_SIZE * 2 + 10];
+        let body = Body::reader(Cursor::new(data.clone()), Some(CHUNK_SIZE as u64 + 5));
+        let chunks = collect(&body);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].len(), CHUNK_SIZE);
+        assert_eq!(chunks[1].len(), 5);
+        // el stream se agota tras la primera escritura
+        assert!(collect(&body).is_empty());
+
+        let body = Body::reader(Cursor::new(data.clone()), None);
+        assert_eq!(body.len(), None);
+        assert_ne!(body, data.as_slice());
+        assert_eq!(body.into_bytes().unwrap(), data);
+    }
+
+    #[test]
+    // prueba de cuerpos producidos por un iterador
+    fn test_iter_body() {
+        let body = Body::iter((1..=3).map(|i| format!("parte {}\n", i).into_bytes()));
+        assert_eq!(body.len(), None);
+        assert!(!body.is_empty());
+        assert_eq!(
+            body.into_bytes().unwrap(),
+            b"parte 1\nparte 2\nparte 3\n".to_vec()
+        );
+    }
+}
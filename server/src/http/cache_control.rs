@@ -0,0 +1,132 @@
+use super::utils::wildcard_match;
+use super::{Response, StatusCode};
+
+/// Criterio con el que una regla de caché elige las responses
+#[derive(Debug, Clone, PartialEq)]
+pub enum CacheMatch {
+    /// Patrón con '*' sobre la ruta completa de la request, p. ej. `/assets/*`
+    Path(String),
+    /// Tipo MIME, exacto o con '*', p. ej. `text/html` o `image/*`
+    Mime(String),
+}
+
+/// Regla que asigna un valor de `Cache-Control` a los archivos que coinciden
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheRule {
+    pub matcher: CacheMatch,
+    pub value: String,
+}
+
+/// Políticas de `Cache-Control` para archivos estáticos, evaluadas en orden de registro
+#[derive(Debug, Clone, Default)]
+pub struct CachePolicy {
+    rules: Vec<CacheRule>,
+}
+
+impl CachePolicy {
+    /// Crea una política vacía
+    pub fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// Asigna `value` a las rutas que coinciden con `pattern`
+    pub fn path(&mut self, pattern: &str, value: &str) {
+        self.rules.push(CacheRule {
+            matcher: CacheMatch::Path(pattern.to_string()),
+            value: value.to_string(),
+        });
+    }
+
+    /// Asigna `value` a los archivos del tipo MIME `mime`
+    pub fn mime(&mut self, mime: &str, value: &str) {
+        self.rules.push(CacheRule {
+            matcher: CacheMatch::Mime(mime.to_string()),
+            value: value.to_string(),
+        });
+    }
+
+    /// Indica si no hay reglas registradas
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Valor de `Cache-Control` de la primera regla que coincide
+    pub fn lookup(&self, path: &str, mime: &str) -> Option<&str> {
+        self.rules
+            .iter()
+            .find(|rule| match &rule.matcher {
+                CacheMatch::Path(pattern) => wildcard_match(pattern, path).is_some(),
+                CacheMatch::Mime(pattern) => {
+                    wildcard_match(&pattern.to_ascii_lowercase(), &mime.to_ascii_lowercase())
+                        .is_some()
+                }
+            })
+            .map(|rule| rule.value.as_str())
+    }
+
+    /// Agrega `Cache-Control` a la response de un archivo si alguna regla coincide
+    /// y el manejador no lo definió
+    pub fn apply(&self, path: &str, mime: &str, res: &mut Response) {
+        let cacheable = res.status == StatusCode::OK
+            || res.status == StatusCode::PARTIALCONTENT
+            || res.status == StatusCode::NOTMODIFIED;
+        if !cacheable || res.headers.get("Cache-Control").is_some() {
+            return;
+        }
+        if let Some(value) = self.lookup(path, mime) {
+            res.headers
+                .insert("Cache-Control".to_string(), value.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    // prueba de reglas por ruta y por tipo MIME, en orden de registro
+    fn test_cache_policy() {
+        let mut policy = CachePolicy::new();
+        assert!(policy.is_empty());
+        policy.path("/assets/*", "public, max-age=31536000, immutable");
+        policy.mime("text/html", "no-cache");
+        policy.mime("image/*", "max-age=3600");
+
+        assert_eq!(
+            policy.lookup("/assets/app.3f2a.js", "text/javascript"),
+            Some("public, max-age=31536000, immutable")
+        );
+        // la primera regla que coincide gana
+        assert_eq!(
+            policy.lookup("/assets/about.html", "text/html"),
+            Some("public, max-age=31536000, immutable")
+        );
+        assert_eq!(policy.lookup("/index.html", "text/html"), Some("no-cache"));
+        assert_eq!(
+            policy.lookup("/logo.png", "image/png"),
+            Some("max-age=3600")
+        );
+        assert_eq!(policy.lookup("/data.bin", "application/octet-stream"), None);
+
+        let mut res = Response::ok("x");
+        policy.apply("/index.html", "text/html", &mut res);
+        assert_eq!(
+            res.headers.get("Cache-Control"),
+            Some(&"no-cache".to_string())
+        );
+
+        // no reemplaza un Cache-Control explícito ni se aplica a errores
+        let mut res = Response::ok("x");
+        res.headers
+            .insert("Cache-Control".to_string(), "private".to_string());
+        policy.apply("/index.html", "text/html", &mut res);
+        assert_eq!(
+            res.headers.get("Cache-Control"),
+            Some(&"private".to_string())
+        );
+        let mut res = Response::not_found();
+        policy.apply("/index.html", "text/html", &mut res);
+        assert!(res.headers.get("Cache-Control").is_none());
+    }
+}
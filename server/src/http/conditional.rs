@@ -0,0 +1,164 @@
+use super::date::{fmt_http_date, parse_http_date};
+use super::{Method, Request, Response, StatusCode};
+use std::fs::Metadata;
+use std::time::{Duration, UNIX_EPOCH};
+
+// Headers que se conservan en una respuesta 304 (RFC 9110 15.4.5)
+const KEPT_IN_304: [&str; 6] = [
+    "ETag",
+    "Last-Modified",
+    "Vary",
+    "Cache-Control",
+    "Expires",
+    "Content-Location",
+];
+
+/// Validadores de un archivo: un ETag basado en tamaño y fecha de modificación,
+/// y esa fecha como `Last-Modified`
+pub fn file_validators(meta: &Metadata) -> (String, Option<String>) {
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok());
+    let etag = match mtime {
+        Some(d) => format!(
+            "\"{:x}-{:x}.{:x}\"",
+            meta.len(),
+            d.as_secs(),
+            d.subsec_nanos()
+        ),
+        None => format!("\"{:x}\"", meta.len()),
+    };
+    // Last-Modified tiene resolución de segundos
+    let last_modified = mtime.map(|d| fmt_http_date(UNIX_EPOCH + Duration::from_secs(d.as_secs())));
+    (etag, last_modified)
+}
+
+// Comparación débil de ETags: se ignora el prefijo W/
+fn weak_eq(a: &str, b: &str) -> bool {
+    a.trim_start_matches("W/") == b.trim_start_matches("W/")
+}
+
+/// Indica si la request condicional puede responderse con 304: `If-None-Match`
+/// coincide con el ETag, o en su ausencia, el recurso no cambió desde `If-Modified-Since`
+pub fn is_not_modified(req: &Request, res: &Response) -> bool {
+    if req.method != Method::GET || res.status != StatusCode::OK {
+        return false;
+    }
+
+    // If-None-Match tiene prioridad sobre If-Modified-Since
+    if let Some(inm) = req.headers.get("If-None-Match") {
+        let etag = match res.headers.get("ETag") {
+            Some(etag) => etag,
+            None => return false,
+        };
+        return inm
+            .split(',')
+            .map(str::trim)
+            .any(|tag| tag == "*" || weak_eq(tag, etag));
+    }
+
+    let since = req
+        .headers
+        .get("If-Modified-Since")
+        .and_then(|s| parse_http_date(s));
+    let modified = res
+        .headers
+        .get("Last-Modified")
+        .and_then(|s| parse_http_date(s));
+    match (since, modified) {
+        (Some(since), Some(modified)) => modified <= since,
+        _ => false,
+    }
+}
+
+/// Respuesta 304 Not Modified, sin cuerpo, con los validadores de `res`
+pub fn not_modified(res: &Response) -> Response {
+    let mut out = Response::builder()
+        .status(StatusCode::NOTMODIFIED)
+        .body(Vec::new());
+    for key in KEPT_IN_304 {
+        if let Some(value) = res.headers.get(key) {
+            out.headers.insert(key.to_string(), value.clone());
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::Headers;
+
+    fn request(headers: Vec<(&str, &str)>) -> Request {
+        Request {
+            headers: Headers::from(&headers),
+            ..Request::new(Method::GET, "/")
+        }
+    }
+
+    fn response() -> Response {
+        Response::builder()
+            .header("ETag", "\"abc\"")
+            .header("Last-Modified", "Sun, 06 Nov 1994 08:49:37 GMT")
+            .header("Content-Type", "text/html")
+            .body("<p>hola</p>")
+    }
+
+    #[test]
+    // prueba de If-None-Match
+    fn test_if_none_match() {
+        let res = response();
+        assert!(is_not_modified(
+            &request(vec![("If-None-Match", "\"abc\"")]),
+            &res
+        ));
+        assert!(is_not_modified(
+            &request(vec![("If-None-Match", "W/\"abc\"")]),
+            &res
+        ));
+        assert!(is_not_modified(
+            &request(vec![("If-None-Match", "\"x\", \"abc\"")]),
+            &res
+        ));
+        assert!(is_not_modified(
+            &request(vec![("If-None-Match", "*")]),
+            &res
+        ));
+        assert!(!is_not_modified(
+            &request(vec![("If-None-Match", "\"x\"")]),
+            &res
+        ));
+        // If-None-Match manda aunque If-Modified-Since diga que no cambió
+        assert!(!is_not_modified(
+            &request(vec![
+                ("If-None-Match", "\"x\""),
+                ("If-Modified-Since", "Mon, 07 Nov 1994 00:00:00 GMT"),
+            ]),
+            &res
+        ));
+    }
+
+    #[test]
+    // prueba de If-Modified-Since
+    fn test_if_modified_since() {
+        let res = response();
+        let check = |date: &str| is_not_modified(&request(vec![("If-Modified-Since", date)]), &res);
+        assert!(check("Sun, 06 Nov 1994 08:49:37 GMT"));
+        assert!(check("Mon, 07 Nov 1994 00:00:00 GMT"));
+        assert!(!check("Sat, 05 Nov 1994 00:00:00 GMT"));
+        assert!(!check("fecha inválida"));
+        assert!(!is_not_modified(&request(vec![]), &res));
+    }
+
+    #[test]
+    // prueba de la respuesta 304
+    fn test_not_modified() {
+        let res = not_modified(&response());
+        assert_eq!(res.status, StatusCode::NOTMODIFIED);
+        assert!(res.body.is_empty());
+        assert_eq!(res.headers.get("ETag"), Some(&"\"abc\"".to_string()));
+        assert!(res.headers.get("Last-Modified").is_some());
+        assert!(res.headers.get("Content-Type").is_none());
+    }
+}
@@ -0,0 +1,124 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const DAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Formatea una fecha como HTTP-date (RFC 9110), p. ej. `Sun, 06 Nov 1994 08:49:37 GMT`
+pub fn fmt_http_date(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let days = (secs / 86400) as i64;
+    let rem = secs % 86400;
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+        DAYS[((days + 4) % 7) as usize],
+        day,
+        MONTHS[(month - 1) as usize],
+        year,
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60
+    )
+}
+
+/// Interpreta una HTTP-date en cualquiera de los tres formatos que exige RFC 9110:
+/// IMF-fixdate (`Sun, 06 Nov 1994 08:49:37 GMT`), RFC 850 (`Sunday, 06-Nov-94 08:49:37 GMT`)
+/// y asctime (`Sun Nov  6 08:49:37 1994`)
+pub fn parse_http_date(s: &str) -> Option<SystemTime> {
+    let parts: Vec<&str> = s.split_whitespace().collect();
+    let (day, month, year, time) = match parts[..] {
+        [_, day, month, year, time, "GMT"] => (day, month, year.parse::<i64>().ok()?, time),
+        [_, date, time, "GMT"] => {
+            let mut date = date.split('-');
+            let (day, month, year) = (date.next()?, date.next()?, date.next()?);
+            // Los años de dos dígitos se interpretan en el siglo pasado o el actual
+            let year = match year.parse::<i64>().ok()? {
+                y if y < 70 => 2000 + y,
+                y if y < 100 => 1900 + y,
+                y => y,
+            };
+            (day, month, year, time)
+        }
+        [_, month, day, time, year] => (day, month, year.parse::<i64>().ok()?, time),
+        _ => return None,
+    };
+
+    let day: u32 = day.parse().ok()?;
+    let month = MONTHS.iter().position(|m| *m == month)? as u32 + 1;
+    let mut time = time.split(':').map(|t| t.parse::<u64>().ok());
+    let (h, m, sec) = (time.next()??, time.next()??, time.next()??);
+    if !(1..=31).contains(&day) || h > 23 || m > 59 || sec > 60 || time.next().is_some() {
+        return None;
+    }
+
+    let days = days_from_civil(year, month, day);
+    if days < 0 {
+        return None;
+    }
+    Some(UNIX_EPOCH + Duration::from_secs(days as u64 * 86400 + h * 3600 + m * 60 + sec))
+}
+
+// Inversa de `civil_from_days`
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+// Convierte días desde 1970-01-01 en (año, mes, día) del calendario gregoriano
+// Algoritmo de Howard Hinnant: http://howardhinnant.github.io/date_algorithms.html
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    // prueba del formato HTTP-date con fechas conocidas
+    fn test_fmt_http_date() {
+        assert_eq!(fmt_http_date(UNIX_EPOCH), "Thu, 01 Jan 1970 00:00:00 GMT");
+        assert_eq!(
+            fmt_http_date(UNIX_EPOCH + Duration::from_secs(784111777)),
+            "Sun, 06 Nov 1994 08:49:37 GMT"
+        );
+        assert_eq!(
+            fmt_http_date(UNIX_EPOCH + Duration::from_secs(951782400)),
+            "Tue, 29 Feb 2000 00:00:00 GMT"
+        );
+    }
+
+    #[test]
+    // prueba de lectura de los tres formatos de HTTP-date
+    fn test_parse_http_date() {
+        let expected = Some(UNIX_EPOCH + Duration::from_secs(784111777));
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), expected);
+        assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), expected);
+        assert_eq!(parse_http_date("Sun Nov  6 08:49:37 1994"), expected);
+
+        let now = UNIX_EPOCH + Duration::from_secs(1_760_000_000);
+        assert_eq!(parse_http_date(&fmt_http_date(now)), Some(now));
+
+        assert_eq!(parse_http_date("Sun, 06 Foo 1994 08:49:37 GMT"), None);
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 25:49:37 GMT"), None);
+        assert_eq!(parse_http_date("ayer"), None);
+        assert_eq!(parse_http_date(""), None);
+    }
+}
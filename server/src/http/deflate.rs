@@ -0,0 +1,329 @@
+// Compresión DEFLATE (RFC 1951) con códigos Huffman fijos, y los formatos
+// gzip (RFC 1952) y zlib (RFC 1950) que la envuelven
+
+// Parámetros de LZ77
+const WINDOW_SIZE: usize = 32 * 1024;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const HASH_BITS: usize = 15;
+// Cuántos candidatos de la cadena de hash se revisan por posición
+const MAX_CHAIN: usize = 64;
+
+// Largos base y bits extra de los códigos 257..=285
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+// Distancias base y bits extra de los códigos 0..=29
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+const CRC_TABLE: [u32; 256] = crc_table();
+
+// Tabla del CRC-32 (polinomio reflejado 0xEDB88320)
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xEDB8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+}
+
+/// CRC-32 usado por gzip
+pub fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &b| {
+        CRC_TABLE[((crc ^ b as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+/// Suma de verificación Adler-32 usada por zlib
+pub fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 es el máximo de bytes que se pueden sumar sin desbordar un u32
+    for block in data.chunks(5552) {
+        for &byte in block {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+// Escribe bits empezando por el menos significativo, como pide DEFLATE
+struct BitWriter {
+    out: Vec<u8>,
+    acc: u64,
+    nbits: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            out: Vec::new(),
+            acc: 0,
+            nbits: 0,
+        }
+    }
+
+    fn write_bits(&mut self, value: u32, count: u32) {
+        self.acc |= (value as u64) << self.nbits;
+        self.nbits += count;
+        while self.nbits >= 8 {
+            self.out.push(self.acc as u8);
+            self.acc >>= 8;
+            self.nbits -= 8;
+        }
+    }
+
+    // Los códigos Huffman se escriben empezando por el bit más significativo
+    fn write_code(&mut self, code: u32, len: u32) {
+        self.write_bits(code.reverse_bits() >> (32 - len), len);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.nbits > 0 {
+            self.out.push(self.acc as u8);
+        }
+        self.out
+    }
+}
+
+// Código Huffman fijo (código, largo) de un símbolo literal/largo
+fn fixed_code(symbol: u16) -> (u32, u32) {
+    let symbol = symbol as u32;
+    match symbol {
+        0..=143 => (0x30 + symbol, 8),
+        144..=255 => (0x190 + symbol - 144, 9),
+        256..=279 => (symbol - 256, 7),
+        _ => (0xC0 + symbol - 280, 8),
+    }
+}
+
+// Índice del último valor base que no supera a `value`
+fn base_index(bases: &[u16], value: u16) -> usize {
+    bases.partition_point(|&b| b <= value) - 1
+}
+
+fn write_literal(w: &mut BitWriter, byte: u8) {
+    let (code, len) = fixed_code(byte as u16);
+    w.write_code(code, len);
+}
+
+fn write_match(w: &mut BitWriter, length: usize, distance: usize) {
+    let i = base_index(&LENGTH_BASE, length as u16);
+    let (code, len) = fixed_code(257 + i as u16);
+    w.write_code(code, len);
+    w.write_bits(
+        (length - LENGTH_BASE[i] as usize) as u32,
+        LENGTH_EXTRA[i] as u32,
+    );
+
+    let i = base_index(&DIST_BASE, distance as u16);
+    w.write_code(i as u32, 5);
+    w.write_bits(
+        (distance - DIST_BASE[i] as usize) as u32,
+        DIST_EXTRA[i] as u32,
+    );
+}
+
+fn hash(data: &[u8], pos: usize) -> usize {
+    let v = (data[pos] as u32) << 16 | (data[pos + 1] as u32) << 8 | data[pos + 2] as u32;
+    (v.wrapping_mul(2_654_435_761) >> (32 - HASH_BITS)) as usize
+}
+
+/// Comprime `data` en un único bloque DEFLATE con códigos Huffman fijos
+pub fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut w = BitWriter::new();
+    // BFINAL = 1, BTYPE = 01 (Huffman fijo)
+    w.write_bits(1, 1);
+    w.write_bits(1, 2);
+
+    // head: última posición vista para cada hash; prev: posición anterior con el mismo hash
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; WINDOW_SIZE];
+    let insert = |pos: usize, head: &mut [usize], prev: &mut [usize]| {
+        if pos + MIN_MATCH <= data.len() {
+            let h = hash(data, pos);
+            prev[pos % WINDOW_SIZE] = head[h];
+            head[h] = pos;
+        }
+    };
+
+    let mut pos = 0;
+    while pos < data.len() {
+        let mut best_len = 0;
+        let mut best_dist = 0;
+        if pos + MIN_MATCH <= data.len() {
+            let max_len = MAX_MATCH.min(data.len() - pos);
+            let mut candidate = head[hash(data, pos)];
+            let mut chain = 0;
+            while candidate != usize::MAX && pos - candidate <= WINDOW_SIZE && chain < MAX_CHAIN {
+                let len = data[candidate..]
+                    .iter()
+                    .zip(&data[pos..pos + max_len])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if len > best_len {
+                    best_len = len;
+                    best_dist = pos - candidate;
+                    if len == max_len {
+                        break;
+                    }
+                }
+                let next = prev[candidate % WINDOW_SIZE];
+                // La entrada pudo ser sobrescrita por una posición más nueva
+                if next == usize::MAX || next >= candidate {
+                    break;
+                }
+                candidate = next;
+                chain += 1;
+            }
+        }
+
+        if best_len >= MIN_MATCH {
+            write_match(&mut w, best_len, best_dist);
+            for p in pos..pos + best_len {
+                insert(p, &mut head, &mut prev);
+            }
+            pos += best_len;
+        } else {
+            write_literal(&mut w, data[pos]);
+            insert(pos, &mut head, &mut prev);
+            pos += 1;
+        }
+    }
+
+    // Fin de bloque
+    let (code, len) = fixed_code(256);
+    w.write_code(code, len);
+    w.finish()
+}
+
+/// Comprime `data` en formato gzip
+pub fn gzip(data: &[u8]) -> Vec<u8> {
+    // Cabecera mínima: magic, método 8 (deflate), sin flags ni fecha, SO desconocido
+    let mut out = vec![0x1F, 0x8B, 8, 0, 0, 0, 0, 0, 0, 0xFF];
+    out.extend(deflate(data));
+    out.extend(crc32(data).to_le_bytes());
+    out.extend((data.len() as u32).to_le_bytes());
+    out
+}
+
+/// Comprime `data` en formato zlib, que es lo que HTTP llama "deflate"
+pub fn zlib(data: &[u8]) -> Vec<u8> {
+    // CMF = deflate con ventana de 32K, FLG elegido para que CMF*256+FLG sea múltiplo de 31
+    let mut out = vec![0x78, 0x01];
+    out.extend(deflate(data));
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Descompresor mínimo de bloques con Huffman fijo, para verificar la compresión
+    fn inflate_fixed(data: &[u8]) -> Vec<u8> {
+        let mut bit = 0;
+        let mut read = |count: u32| {
+            let mut v = 0;
+            for i in 0..count {
+                v |= (((data[bit / 8] >> (bit % 8)) & 1) as u32) << i;
+                bit += 1;
+            }
+            v
+        };
+        assert_eq!(read(1), 1);
+        assert_eq!(read(2), 1);
+
+        let mut out: Vec<u8> = Vec::new();
+        loop {
+            // Lee un código MSB primero y busca el símbolo fijo que le corresponde
+            let mut code = 0;
+            let mut len = 0;
+            let symbol = loop {
+                code = (code << 1) | read(1);
+                len += 1;
+                if let Some(s) = (0..=287).find(|&s| fixed_code(s) == (code, len)) {
+                    break s;
+                }
+            };
+            match symbol {
+                0..=255 => out.push(symbol as u8),
+                256 => return out,
+                _ => {
+                    let i = (symbol - 257) as usize;
+                    let length = LENGTH_BASE[i] as usize + read(LENGTH_EXTRA[i] as u32) as usize;
+                    let d = (0..5).fold(0, |acc, _| (acc << 1) | read(1)) as usize;
+                    let dist = DIST_BASE[d] as usize + read(DIST_EXTRA[d] as u32) as usize;
+                    for _ in 0..length {
+                        out.push(out[out.len() - dist]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    // prueba de las sumas de verificación con valores conocidos
+    fn test_checksums() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+        assert_eq!(adler32(b""), 1);
+    }
+
+    #[test]
+    // prueba de ida y vuelta de la compresión
+    fn test_deflate_roundtrip() {
+        let text = "<li>elemento repetido</li>\n".repeat(200);
+        let compressed = deflate(text.as_bytes());
+        assert!(compressed.len() < text.len() / 10);
+        assert_eq!(inflate_fixed(&compressed), text.as_bytes());
+
+        let mixed: Vec<u8> = (0..5000u32).map(|i| (i * i % 251) as u8).collect();
+        assert_eq!(inflate_fixed(&deflate(&mixed)), mixed);
+        assert_eq!(inflate_fixed(&deflate(b"")), b"");
+        assert_eq!(inflate_fixed(&deflate(b"ab")), b"ab");
+    }
+
+    #[test]
+    // prueba de las envolturas gzip y zlib
+    fn test_gzip_zlib() {
+        let data = b"hola hola hola hola";
+        let gz = gzip(data);
+        assert_eq!(&gz[..3], &[0x1F, 0x8B, 8]);
+        assert_eq!(gz[gz.len() - 8..gz.len() - 4], crc32(data).to_le_bytes());
+        assert_eq!(gz[gz.len() - 4..], (data.len() as u32).to_le_bytes());
+        assert_eq!(inflate_fixed(&gz[10..gz.len() - 8]), data);
+
+        let z = zlib(data);
+        assert_eq!((z[0] as u16 * 256 + z[1] as u16) % 31, 0);
+        assert_eq!(z[z.len() - 4..], adler32(data).to_be_bytes());
+        assert_eq!(inflate_fixed(&z[2..z.len() - 4]), data);
+    }
+}
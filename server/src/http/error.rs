@@ -0,0 +1,226 @@
+use super::utils::{html_escape, json_escape};
+use super::{Headers, Response, StatusCode, TemplateError};
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::num::ParseIntError;
+
+/// Error HTTP que un manejador puede devolver con `?`.
+/// Lleva el código de estado, un mensaje para el cliente y opcionalmente la causa
+pub struct HttpError {
+    pub status: StatusCode,
+    pub message: String,
+    source: Option<Box<dyn Error + Send + Sync>>,
+}
+
+impl HttpError {
+    /// Crea un error con un código y mensaje
+    pub fn new(status: StatusCode, message: &str) -> Self {
+        Self {
+            status,
+            message: message.to_string(),
+            source: None,
+        }
+    }
+
+    /// Error 400 Bad Request
+    pub fn bad_request(message: &str) -> Self {
+        Self::new(StatusCode::BADREQUEST, message)
+    }
+
+    /// Error 404 Not Found
+    pub fn not_found(message: &str) -> Self {
+        Self::new(StatusCode::NOTFOUND, message)
+    }
+
+    /// Error 500 Internal Server Error
+    pub fn internal(message: &str) -> Self {
+        Self::new(StatusCode::INTERNALERR, message)
+    }
+
+    /// Adjunta la causa original del error
+    pub fn with_source<E: Error + Send + Sync + 'static>(mut self, source: E) -> Self {
+        self.source = Some(Box::new(source));
+        self
+    }
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.status.as_u16(), self.message)
+    }
+}
+
+impl fmt::Debug for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpError")
+            .field("status", &self.status)
+            .field("message", &self.message)
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+impl Error for HttpError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_deref().map(|e| e as &(dyn Error + 'static))
+    }
+}
+
+// Los errores de parseo del propio módulo (p. ej. `parse_url_param`) son errores del cliente
+impl From<&'static str> for HttpError {
+    fn from(message: &'static str) -> Self {
+        Self::bad_request(message)
+    }
+}
+
+impl From<ParseIntError> for HttpError {
+    fn from(err: ParseIntError) -> Self {
+        Self::bad_request("Invalid number").with_source(err)
+    }
+}
+
+impl From<io::Error> for HttpError {
+    fn from(err: io::Error) -> Self {
+        Self::internal("I/O error").with_source(err)
+    }
+}
+
+impl From<TemplateError> for HttpError {
+    fn from(err: TemplateError) -> Self {
+        Self::internal("Template error").with_source(err)
+    }
+}
+
+/// Formato con el que se muestran los `HttpError` al cliente
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorFormat {
+    Text,
+    Html,
+    Json,
+}
+
+impl ErrorFormat {
+    /// Genera la response que describe el error en este formato
+    pub fn render(&self, err: &HttpError) -> Response {
+        let code = err.status.as_u16();
+        let (content_type, body) = match self {
+            ErrorFormat::Text => ("text/plain", err.message.clone()),
+            ErrorFormat::Html => (
+                "text/html; charset=utf-8",
+                format!(
+                    "<!DOCTYPE html>\n<html>\n<head><title>Error {code}</title></head>\n\
+                     <body>\n<h1>Error {code}</h1>\n<p>{}</p>\n</body>\n</html>\n",
+                    html_escape(&err.message)
+                ),
+            ),
+            ErrorFormat::Json => (
+                "application/json",
+                format!(
+                    "{{\"status\":{},\"error\":\"{}\"}}",
+                    code,
+                    json_escape(&err.message)
+                ),
+            ),
+        };
+        Response {
+            status: err.status,
+            headers: Headers::from(&vec![("Content-Type", content_type)]),
+            body: body.into(),
+            trailers: Headers::from(&vec![]),
+            error: None,
+            from_handler: false,
+        }
+    }
+}
+
+/// Tipos que un manejador puede devolver
+pub trait IntoResponse {
+    fn into_response(self) -> Response;
+}
+
+impl IntoResponse for Response {
+    fn into_response(self) -> Response {
+        self
+    }
+}
+
+impl IntoResponse for HttpError {
+    /// Genera una response de texto que conserva el error para que el
+    /// servidor la vuelva a formatear con su `ErrorFormat`
+    fn into_response(self) -> Response {
+        let mut res = ErrorFormat::Text.render(&self);
+        res.error = Some(self);
+        res
+    }
+}
+
+impl<E: Into<HttpError>> IntoResponse for Result<Response, E> {
+    fn into_response(self) -> Response {
+        match self {
+            Ok(res) => res,
+            Err(err) => err.into().into_response(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::parse_url_param;
+
+    // Manejador de ejemplo que usa `?` con errores de parseo
+    fn handler(body: &str) -> Result<Response, HttpError> {
+        let params = parse_url_param(body)?;
+        let age: u32 = params
+            .get("age")
+            .ok_or_else(|| HttpError::bad_request("Missing age"))?
+            .parse()?;
+        Ok(Response::ok(&age.to_string()))
+    }
+
+    #[test]
+    // prueba de la conversión de errores con `?`
+    fn test_question_mark() {
+        let res = handler("age=30").into_response();
+        assert_eq!(res.status, StatusCode::OK);
+        assert!(res.error.is_none());
+
+        let res = handler("age").into_response();
+        assert_eq!(res.status, StatusCode::BADREQUEST);
+        assert_eq!(res.body, b"Invalid URL param");
+
+        let res = handler("name=ana").into_response();
+        assert_eq!(res.body, b"Missing age");
+
+        let res = handler("age=old").into_response();
+        let err = res.error.unwrap();
+        assert_eq!(err.message, "Invalid number");
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    // prueba de los formatos de error
+    fn test_error_format() {
+        let err = HttpError::not_found("No user \"ana\" <here>");
+
+        let res = ErrorFormat::Text.render(&err);
+        assert_eq!(res.status, StatusCode::NOTFOUND);
+        assert_eq!(res.body, b"No user \"ana\" <here>");
+
+        let res = ErrorFormat::Html.render(&err);
+        let html = String::from_utf8(res.body.into_bytes().unwrap()).unwrap();
+        assert!(html.contains("<h1>Error 404</h1>"));
+        assert!(html.contains("<p>No user &quot;ana&quot; &lt;here&gt;</p>"));
+
+        let res = ErrorFormat::Json.render(&err);
+        assert_eq!(
+            res.headers.get("Content-Type"),
+            Some(&"application/json".to_string())
+        );
+        assert_eq!(
+            res.body,
+            b"{\"status\":404,\"error\":\"No user \\\"ana\\\" <here>\"}"
+        );
+    }
+}
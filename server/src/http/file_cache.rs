@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, Metadata};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Contenido de un archivo cacheado, compartido entre las responses que lo usan
+#[derive(Debug, Clone)]
+pub struct CachedBytes(Arc<Vec<u8>>);
+
+impl AsRef<[u8]> for CachedBytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+// Entrada de la caché; `modified` y `len` detectan cambios en disco
+struct Entry {
+    data: CachedBytes,
+    modified: Option<SystemTime>,
+    len: u64,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: HashMap<PathBuf, Entry>,
+    bytes: u64,
+    // Reloj lógico para saber qué entrada se usó hace más tiempo
+    tick: u64,
+}
+
+/// Estadísticas de uso de la caché
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub bytes: u64,
+    pub capacity: u64,
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "hits={} misses={} entries={} bytes={}/{}",
+            self.hits, self.misses, self.entries, self.bytes, self.capacity
+        )
+    }
+}
+
+/// Caché en memoria del contenido de archivos, acotada en bytes y con desalojo LRU.
+/// Cada acceso compara la fecha de modificación y el tamaño con los del disco,
+/// así que un archivo modificado se vuelve a leer
+pub struct FileCache {
+    inner: Mutex<Inner>,
+    capacity: u64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl FileCache {
+    /// Crea una caché que guarda hasta `capacity` bytes de contenido
+    pub fn new(capacity: u64) -> Self {
+        Self {
+            inner: Mutex::new(Inner::default()),
+            capacity,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Devuelve el contenido de `path` y sus metadatos, desde memoria si está
+    /// vigente o leyéndolo del disco. Los archivos más grandes que la caché no se
+    /// leen ni se guardan: devuelve `None` para que se envíen desde el disco
+    pub fn get(&self, path: &Path) -> io::Result<Option<(CachedBytes, Metadata)>> {
+        let meta = fs::metadata(path)?;
+        let modified = meta.modified().ok();
+
+        if meta.len() > self.capacity {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            // Una versión anterior más chica pudo quedar en la caché
+            let mut inner = self.inner.lock().unwrap();
+            if let Some(old) = inner.entries.remove(path) {
+                inner.bytes -= old.len;
+            }
+            return Ok(None);
+        }
+
+        {
+            let mut inner = self.inner.lock().unwrap();
+            inner.tick += 1;
+            let tick = inner.tick;
+            if let Some(entry) = inner.entries.get_mut(path) {
+                if entry.modified == modified && entry.len == meta.len() {
+                    entry.last_used = tick;
+                    self.hits.fetch_add(1, Ordering::Relaxed);
+                    return Ok(Some((entry.data.clone(), meta)));
+                }
+            }
+        }
+
+        // La lectura se hace sin tener tomado el lock
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let data = CachedBytes(Arc::new(fs::read(path)?));
+        let len = data.0.len() as u64;
+
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(old) = inner.entries.remove(path) {
+            inner.bytes -= old.len;
+        }
+        if len <= self.capacity {
+            while inner.bytes + len > self.capacity {
+                let oldest = inner
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, e)| e.last_used)
+                    .map(|(p, _)| p.clone());
+                match oldest.and_then(|p| inner.entries.remove(&p)) {
+                    Some(evicted) => inner.bytes -= evicted.len,
+                    None => break,
+                }
+            }
+            inner.tick += 1;
+            let entry = Entry {
+                data: data.clone(),
+                modified,
+                len,
+                last_used: inner.tick,
+            };
+            inner.entries.insert(path.to_path_buf(), entry);
+            inner.bytes += len;
+        }
+        Ok(Some((data, meta)))
+    }
+
+    /// Estadísticas de aciertos, fallos y ocupación
+    pub fn stats(&self) -> CacheStats {
+        let inner = self.inner.lock().unwrap();
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: inner.entries.len(),
+            bytes: inner.bytes,
+            capacity: self.capacity,
+        }
+    }
+
+    /// Vacía la caché, sin reiniciar los contadores
+    pub fn clear(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.entries.clear();
+        inner.bytes = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::time::Duration;
+
+    #[test]
+    // prueba de aciertos, fallos, desalojo LRU e invalidación por cambios en disco
+    fn test_file_cache() {
+        let dir = env::temp_dir().join(format!("file-cache-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (a, b, c) = (dir.join("a"), dir.join("b"), dir.join("c"));
+        fs::write(&a, "aaaa").unwrap();
+        fs::write(&b, "bbbb").unwrap();
+        fs::write(&c, "cccc").unwrap();
+
+        let cache = FileCache::new(10);
+        assert_eq!(cache.get(&a).unwrap().unwrap().0.as_ref(), b"aaaa");
+        assert_eq!(cache.get(&a).unwrap().unwrap().0.as_ref(), b"aaaa");
+        cache.get(&b).unwrap();
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 2));
+        assert_eq!((stats.entries, stats.bytes), (2, 8));
+
+        // `a` se usó antes que `b`, así que `c` lo desaloja
+        cache.get(&c).unwrap();
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.bytes), (2, 8));
+        cache.get(&b).unwrap();
+        assert_eq!(cache.stats().hits, 2);
+        cache.get(&a).unwrap();
+        assert_eq!(cache.stats().misses, 4);
+
+        // un archivo modificado se vuelve a leer
+        fs::write(&a, "nuevo").unwrap();
+        let file = fs::File::options().write(true).open(&a).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(cache.get(&a).unwrap().unwrap().0.as_ref(), b"nuevo");
+        assert_eq!(cache.stats().misses, 5);
+
+        // los archivos más grandes que la caché no se guardan
+        let big = dir.join("big");
+        fs::write(&big, "x".repeat(11)).unwrap();
+        assert!(cache.get(&big).unwrap().is_none());
+        assert!(cache.stats().bytes <= 10);
+        assert_eq!(cache.stats().misses, 6);
+
+        cache.clear();
+        assert_eq!(cache.stats().entries, 0);
+        assert!(cache.get(&dir.join("missing")).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,356 @@
+use super::date::fmt_http_date;
+use super::utils::{html_escape, json_escape, percent_decode, percent_encode};
+use super::{mime_type, Request, Response};
+use std::cmp::Ordering;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Entrada de un listado de directorio
+struct Entry {
+    name: String,
+    is_dir: bool,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+/// Sirve el archivo de `root` que corresponde a la ruta (ya sin prefijo) de la request.
+/// Si `listing` está activo, los directorios sin `index.html` muestran su contenido
+pub fn serve_dir(req: &Request, root: &str, listing: bool) -> Response {
+    let path = match resolve(root, req.path.as_str()) {
+        Some(p) => p,
+        None => return Response::not_found(),
+    };
+
+    if !path.is_dir() {
+        return Response::file_for(req, path);
+    }
+
+    // Los directorios se piden con '/' final para que los enlaces relativos funcionen;
+    // la redirección conserva los parámetros de la URL
+    if !req.path.as_str().ends_with('/') {
+        if let Some(alt) = req.path.toggle_trailing_slash() {
+            return Response::redirect(&alt.to_string());
+        }
+    }
+
+    match resolve_child(root, &path, "index.html") {
+        Some(index) => Response::file_for(req, index),
+        None if listing => list_dir(req, root, &path),
+        None => Response::not_found(),
+    }
+}
+
+/// Genera el listado de un directorio en HTML, o en JSON si el cliente lo pide
+fn list_dir(req: &Request, root: &str, dir: &Path) -> Response {
+    let read = match fs::read_dir(dir) {
+        Ok(r) => r,
+        Err(_) => return Response::internal_err("Could not read directory"),
+    };
+
+    // Se omiten los archivos ocultos y los enlaces que apuntan fuera de la raíz
+    let mut entries: Vec<Entry> = read
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().into_string().ok()?;
+            if name.starts_with('.') {
+                return None;
+            }
+            let path = resolve_child_any(root, dir, &name)?;
+            let meta = fs::metadata(path).ok()?;
+            Some(Entry {
+                name,
+                is_dir: meta.is_dir(),
+                size: meta.len(),
+                modified: meta.modified().ok(),
+            })
+        })
+        .collect();
+
+    let params = req.path.parse_params().unwrap_or_default();
+    let sort = params.get("sort").copied().unwrap_or("name");
+    let desc = params.get("order") == Some(&"desc");
+    sort_entries(&mut entries, sort, desc);
+
+    let location = format!("{}{}", req.path.base(), req.path.as_str());
+    let wants_json = req
+        .headers
+        .get("Accept")
+        .is_some_and(|a| a.contains("application/json"));
+
+    let (body, content_type) = if wants_json {
+        (listing_json(&location, &entries), "application/json")
+    } else {
+        (
+            listing_html(&location, &entries, sort, desc),
+            "text/html; charset=utf-8",
+        )
+    };
+    let mut res = Response::ok(&body);
+    res.headers
+        .insert("Content-Type".to_string(), content_type.to_string());
+    res
+}
+
+/// Ordena las entradas por nombre, tamaño o fecha, dejando siempre los directorios primero
+fn sort_entries(entries: &mut [Entry], sort: &str, desc: bool) {
+    entries.sort_by(|a, b| {
+        let order = match sort {
+            "size" => a.size.cmp(&b.size),
+            "modified" => a.modified.cmp(&b.modified),
+            _ => Ordering::Equal,
+        }
+        .then_with(|| a.name.cmp(&b.name));
+        let order = if desc { order.reverse() } else { order };
+        b.is_dir.cmp(&a.is_dir).then(order)
+    });
+}
+
+/// Tipo MIME que se muestra para una entrada
+fn entry_mime(entry: &Entry) -> &str {
+    if entry.is_dir {
+        "inode/directory"
+    } else {
+        mime_type(Path::new(&entry.name))
+    }
+}
+
+fn listing_json(location: &str, entries: &[Entry]) -> String {
+    let items: Vec<String> = entries
+        .iter()
+        .map(|e| {
+            format!(
+                "{{\"name\":\"{}\",\"type\":\"{}\",\"size\":{},\"modified\":{},\"mime\":\"{}\"}}",
+                json_escape(&e.name),
+                if e.is_dir { "dir" } else { "file" },
+                e.size,
+                e.modified
+                    .map_or("null".to_string(), |m| format!("\"{}\"", fmt_http_date(m))),
+                entry_mime(e)
+            )
+        })
+        .collect();
+    format!(
+        "{{\"path\":\"{}\",\"entries\":[{}]}}",
+        json_escape(location),
+        items.join(",")
+    )
+}
+
+fn listing_html(location: &str, entries: &[Entry], sort: &str, desc: bool) -> String {
+    // Migas de pan: un enlace por cada segmento de la ruta
+    let mut crumbs = String::from("<a href=\"/\">/</a>");
+    let mut href = String::from("/");
+    for segment in location.split('/').filter(|s| !s.is_empty()) {
+        href.push_str(segment);
+        href.push('/');
+        crumbs.push_str(&format!(
+            "<a href=\"{}\">{}</a>/",
+            html_escape(&href),
+            html_escape(&percent_decode(segment).unwrap_or_else(|| segment.to_string()))
+        ));
+    }
+
+    // Cada columna enlaza a su orden ascendente, o al descendente si ya está activa
+    let header = |key: &str, label: &str| {
+        let order = if sort == key && !desc { "desc" } else { "asc" };
+        format!(
+            "<th><a href=\"?sort={}&amp;order={}\">{}</a></th>",
+            key, order, label
+        )
+    };
+
+    let mut rows = String::new();
+    if location != "/" {
+        rows.push_str("<tr><td><a href=\"../\">../</a></td><td></td><td></td><td></td></tr>\n");
+    }
+    for e in entries {
+        let suffix = if e.is_dir { "/" } else { "" };
+        rows.push_str(&format!(
+            "<tr><td><a href=\"{}{}\">{}{}</a></td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            percent_encode(&e.name),
+            suffix,
+            html_escape(&e.name),
+            suffix,
+            if e.is_dir {
+                "-".to_string()
+            } else {
+                e.size.to_string()
+            },
+            e.modified.map_or(String::new(), fmt_http_date),
+            entry_mime(e)
+        ));
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"UTF-8\"><title>Index of {title}</title></head>\n\
+         <body>\n<h1>Index of {crumbs}</h1>\n<table>\n<tr>{}{}{}<th>Type</th></tr>\n{rows}</table>\n</body>\n</html>\n",
+        header("name", "Name"),
+        header("size", "Size"),
+        header("modified", "Modified"),
+        title = html_escape(location),
+    )
+}
+
+/// Convierte una ruta de request en un archivo existente dentro de `root`.
+/// Devuelve `None` si la ruta es inválida, no existe o escapa de `root`
+pub fn resolve(root: &str, req_path: &str) -> Option<PathBuf> {
+    // Decodifica antes de validar para detectar también `%2e%2e` y `%2f`
+    let decoded = percent_decode(req_path)?;
+
+    let mut relative = PathBuf::new();
+    for segment in decoded.split('/') {
+        match segment {
+            "" | "." => continue,
+            ".." => return None,
+            s if s.contains('\\') || s.contains('\0') => return None,
+            s => relative.push(s),
+        }
+    }
+
+    let root = fs::canonicalize(root).ok()?;
+    let path = fs::canonicalize(root.join(relative)).ok()?;
+
+    // canonicalize resuelve los enlaces simbólicos, así que un enlace que apunte
+    // fuera de la raíz queda fuera de `root`
+    path.starts_with(&root).then_some(path)
+}
+
+/// Resuelve un archivo dentro de un directorio ya validado
+fn resolve_child(root: &str, dir: &Path, name: &str) -> Option<PathBuf> {
+    resolve_child_any(root, dir, name).filter(|p| p.is_file())
+}
+
+/// Resuelve un archivo o directorio dentro de un directorio ya validado
+fn resolve_child_any(root: &str, dir: &Path, name: &str) -> Option<PathBuf> {
+    let root = fs::canonicalize(root).ok()?;
+    let path = fs::canonicalize(dir.join(name)).ok()?;
+    path.starts_with(&root).then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{Headers, Method, StatusCode};
+    use std::env;
+
+    // Crea un directorio temporal con la estructura usada en las pruebas
+    fn fixture(name: &str) -> PathBuf {
+        let base = env::temp_dir().join(format!("http-files-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        fs::create_dir_all(base.join("public/docs")).unwrap();
+        fs::write(base.join("public/index.html"), "<h1>home</h1>").unwrap();
+        fs::write(base.join("public/docs/a b.txt"), "spaced").unwrap();
+        fs::write(base.join("secret.txt"), "secret").unwrap();
+        base
+    }
+
+    fn request(path: &str) -> Request {
+        request_accepting(path, "*/*")
+    }
+
+    fn request_accepting(path: &str, accept: &str) -> Request {
+        Request {
+            headers: Headers::from(&vec![("Accept", accept)]),
+            ..Request::new(Method::GET, path)
+        }
+    }
+
+    #[test]
+    // prueba de resolución de rutas válidas y de intentos de escapar de la raíz
+    fn test_resolve() {
+        let base = fixture("resolve");
+        let root = base.join("public");
+        let root = root.to_str().unwrap();
+
+        assert!(resolve(root, "/index.html").is_some());
+        assert!(resolve(root, "/docs/a%20b.txt").is_some());
+        assert!(resolve(root, "/docs/./a%20b.txt").is_some());
+        assert!(resolve(root, "/missing.html").is_none());
+        assert!(resolve(root, "/../secret.txt").is_none());
+        assert!(resolve(root, "/%2e%2e/secret.txt").is_none());
+        assert!(resolve(root, "/docs%2f..%2f..%2fsecret.txt").is_none());
+        assert!(resolve(root, "/..%5csecret.txt").is_none());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(base.join("secret.txt"), base.join("public/link.txt"))
+                .unwrap();
+            assert!(resolve(root, "/link.txt").is_none());
+        }
+
+        fs::remove_dir_all(base).unwrap();
+    }
+
+    #[test]
+    // prueba de archivos, índices de directorio y redirección con '/' final
+    fn test_serve_dir() {
+        let base = fixture("serve");
+        let root = base.join("public");
+        let root = root.to_str().unwrap();
+
+        let res = serve_dir(&request("/docs/a%20b.txt"), root, false);
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.body.into_bytes().unwrap(), b"spaced");
+        assert_eq!(
+            res.headers.get("Content-Type"),
+            Some(&"text/plain".to_string())
+        );
+
+        let res = serve_dir(&request("/"), root, false);
+        assert_eq!(res.body.into_bytes().unwrap(), b"<h1>home</h1>");
+
+        let res = serve_dir(&request("/docs"), root, false);
+        assert_eq!(res.status, StatusCode::REDIRECT);
+        assert_eq!(res.headers.get("Location"), Some(&"/docs/".to_string()));
+
+        let res = serve_dir(&request("/docs?sort=size&order=desc"), root, false);
+        assert_eq!(
+            res.headers.get("Location"),
+            Some(&"/docs/?sort=size&order=desc".to_string())
+        );
+
+        let res = serve_dir(&request("/docs/"), root, false);
+        assert_eq!(res.status, StatusCode::NOTFOUND);
+
+        fs::remove_dir_all(base).unwrap();
+    }
+
+    #[test]
+    // prueba de listados de directorio en HTML y JSON
+    fn test_listing() {
+        let base = fixture("listing");
+        let root = base.join("public");
+        fs::write(root.join("docs/big.js"), "x".repeat(100)).unwrap();
+        fs::write(root.join("docs/.hidden"), "h").unwrap();
+        fs::create_dir(root.join("docs/sub")).unwrap();
+        let root = root.to_str().unwrap();
+
+        let res = serve_dir(&request("/docs/?sort=size&order=desc"), root, true);
+        let html = String::from_utf8(res.body.into_bytes().unwrap()).unwrap();
+        assert_eq!(
+            res.headers.get("Content-Type"),
+            Some(&"text/html; charset=utf-8".to_string())
+        );
+        assert!(!html.contains(".hidden"));
+        let sub = html.find("sub/").unwrap();
+        let big = html.find("big.js").unwrap();
+        let spaced = html.find("a%20b.txt").unwrap();
+        assert!(sub < big && big < spaced);
+        assert!(html.contains("<td>text/javascript</td>"));
+        assert!(html.contains("href=\"?sort=size&amp;order=asc\""));
+
+        let res = serve_dir(&request_accepting("/docs/", "application/json"), root, true);
+        let json = String::from_utf8(res.body.into_bytes().unwrap()).unwrap();
+        assert!(json
+            .starts_with("{\"path\":\"/docs/\",\"entries\":[{\"name\":\"sub\",\"type\":\"dir\""));
+        assert!(json.contains("{\"name\":\"a b.txt\",\"type\":\"file\",\"size\":6,"));
+        assert!(json.contains("\"mime\":\"text/plain\"}"));
+
+        // Los directorios con index.html no se listan
+        let res = serve_dir(&request("/"), root, true);
+        assert_eq!(res.body.into_bytes().unwrap(), b"<h1>home</h1>");
+
+        fs::remove_dir_all(base).unwrap();
+    }
+}
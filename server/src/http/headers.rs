@@ -8,6 +8,7 @@ use std::str::Split;
 type HeadersDataType = HashMap<String, String>;
 
 // Definimos la estructura principal
+#[derive(Clone)]
 pub struct Headers {
     data: HeadersDataType,
 }
@@ -19,8 +20,14 @@ impl Headers {
     }
 
     // Método genérico para obtener el valor de un encabezado por su clave
+    // Los nombres de encabezado no distinguen mayúsculas (RFC 9110)
     pub fn get(&self, key: &str) -> Option<&String> {
-        self.data.get(key)
+        self.data.get(key).or_else(|| {
+            self.data
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| v)
+        })
     }
 
     // Método para obtener un iterador sobre las entradas (clave, valor) del HashMap
@@ -28,11 +35,27 @@ impl Headers {
         self.data.iter()
     }
 
-    // Método para agregar un nuevo encabezado
+    // Método para agregar un encabezado; reemplaza al existente aunque difiera en mayúsculas
     pub fn insert(&mut self, key: String, value: String) {
+        self.remove(&key);
         self.data.insert(key, value);
     }
 
+    // Método para quitar un encabezado, sin distinguir mayúsculas
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let key = self
+            .data
+            .keys()
+            .find(|k| k.eq_ignore_ascii_case(key))?
+            .clone();
+        self.data.remove(&key)
+    }
+
+    // Método para saber si no hay encabezados
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
     // Método para convertir una línea en clave-valor
     fn parse_header_line(line: &str) -> Result<(String, String), &'static str> {
         let mut line_splitted = line.splitn(2, ':');
@@ -128,6 +151,10 @@ mod tests {
             Some(&"application/json".to_string())
         );
         assert_eq!(headers.get("No_existe"), None);
+        assert_eq!(
+            headers.get("content-type"),
+            Some(&"application/json".to_string())
+        );
     }
 
     // Prueba  iter (). Se asegura primero que hayan 2 elementos en el headers y luego verfica que sean los elementos esperados
@@ -155,6 +182,21 @@ mod tests {
         headers.insert("key".to_string(), "val".to_string());
         assert_eq!(headers.get("key"), Some(&"val".to_string()));
     }
+
+    #[test]
+    // Prueba que insert () reemplaza un encabezado con otras mayúsculas en lugar de duplicarlo
+    fn test_insert_ignores_case() {
+        let mut headers = Headers {
+            data: HeadersDataType::new(),
+        };
+        headers.insert("content-length".to_string(), "10".to_string());
+        headers.insert("Content-Length".to_string(), "20".to_string());
+        let collected: Vec<(&String, &String)> = headers.iter().collect();
+        assert_eq!(
+            collected,
+            vec![(&"Content-Length".to_string(), &"20".to_string())]
+        );
+    }
     #[test]
     // Prueba parse_header_line con 1 entrada válidas e 2 inválidas
     fn test_parse_header_line() {
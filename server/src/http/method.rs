@@ -1,6 +1,7 @@
 use std::convert::From;
 
-#[derive(Debug, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Method {
     GET,
     POST,
@@ -0,0 +1,384 @@
+use super::deflate::{gzip, zlib};
+use super::utils::negotiate_encoding;
+use super::{Request, Response, StatusCode};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+/// Middleware que envuelve el manejo de una request.
+///
+/// Lo más simple es implementar `before` (puede modificar la request o cortar la
+/// cadena devolviendo su propia `Response`) y `after` (puede modificar la response).
+/// Para control total, como medir tiempos, se puede sobrescribir `handle`.
+pub trait Middleware: Send + Sync {
+    /// Se ejecuta antes del manejador; si devuelve una response, la cadena se corta
+    fn before(&self, _req: &mut Request) -> Option<Response> {
+        None
+    }
+
+    /// Se ejecuta después del manejador con la response generada
+    fn after(&self, _req: &Request, _res: &mut Response) {}
+
+    /// Ejecuta el middleware y el resto de la cadena
+    fn handle(&self, req: &mut Request, next: Next) -> Response {
+        if let Some(res) = self.before(req) {
+            return res;
+        }
+        let mut res = next.run(req);
+        self.after(req, &mut res);
+        res
+    }
+}
+
+/// Resto de la cadena de middlewares, terminando en el manejador final
+pub struct Next<'a> {
+    chain: &'a [Arc<dyn Middleware>],
+    endpoint: &'a dyn Fn(&mut Request) -> Response,
+}
+
+impl<'a> Next<'a> {
+    /// Crea una cadena que ejecuta `chain` en orden y luego `endpoint`
+    pub fn new(
+        chain: &'a [Arc<dyn Middleware>],
+        endpoint: &'a dyn Fn(&mut Request) -> Response,
+    ) -> Self {
+        Self { chain, endpoint }
+    }
+
+    /// Ejecuta el siguiente middleware, o el manejador final si no quedan
+    pub fn run(self, req: &mut Request) -> Response {
+        match self.chain.split_first() {
+            Some((first, rest)) => first.handle(req, Next::new(rest, self.endpoint)),
+            None => (self.endpoint)(req),
+        }
+    }
+}
+
+/// Devuelve en `Set-Cookie` las cookies recibidas en el header `Cookie`
+pub struct CookieEcho;
+
+impl Middleware for CookieEcho {
+    fn after(&self, req: &Request, res: &mut Response) {
+        // Verificar si en los headers del request hay cookies
+        let cookies = match req.headers.get("Cookie") {
+            Some(c) => c,
+            None => return,
+        };
+
+        // Parsear las cookies
+        let mut cookie_map = HashMap::new();
+        for cookie in cookies.split(';') {
+            let mut parts = cookie.split('=');
+            if let (Some(key), Some(value)) = (parts.next(), parts.next()) {
+                cookie_map.insert(key.trim().to_string(), value.trim().to_string());
+            }
+        }
+
+        if !cookie_map.is_empty() {
+            res.set_cookie(cookie_map);
+        }
+    }
+}
+
+/// Imprime una línea de registro por cada request procesada
+pub struct Logger;
+
+impl Middleware for Logger {
+    fn handle(&self, req: &mut Request, next: Next) -> Response {
+        let start = Instant::now();
+        let res = next.run(req);
+        let duration = start.elapsed();
+
+        println!(
+            "#{} [{}] {{{}}} {:?} '{}' -> {} {:.2}ms",
+            thread::current().name().unwrap_or("main"),
+            req.peer_addr
+                .map_or_else(|| "unknown".to_string(), |a| a.to_string()),
+            req.headers.user_agent().unwrap_or(&String::from("None")),
+            req.method,
+            req.path,
+            res.status.as_u16(),
+            duration.as_nanos() as f64 / 1e+6
+        );
+        res
+    }
+}
+
+/// Comprime con gzip o deflate las responses de tipos comprimibles, según `Accept-Encoding`.
+/// Los cuerpos se comprimen en memoria, así que solo se aplica a tamaños conocidos y acotados
+pub struct Compression {
+    min_size: u64,
+    max_size: u64,
+}
+
+impl Compression {
+    /// Compresión para cuerpos de entre 1 KiB y 16 MiB
+    pub fn new() -> Self {
+        Self {
+            min_size: 1024,
+            max_size: 16 * 1024 * 1024,
+        }
+    }
+
+    /// Tamaño mínimo del cuerpo, en bytes, a partir del cual se comprime
+    pub fn min_size(mut self, bytes: u64) -> Self {
+        self.min_size = bytes;
+        self
+    }
+
+    /// Tamaño máximo del cuerpo, en bytes, que se comprime
+    pub fn max_size(mut self, bytes: u64) -> Self {
+        self.max_size = bytes;
+        self
+    }
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Indica si vale la pena comprimir un `Content-Type`; los formatos ya comprimidos
+/// (PNG, JPEG, WebP, etc.) no ganan nada
+pub fn is_compressible(content_type: &str) -> bool {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    mime.starts_with("text/")
+        || mime.ends_with("+xml")
+        || mime.ends_with("+json")
+        || matches!(
+            mime.as_str(),
+            "application/javascript" | "application/json" | "application/xml" | "application/wasm"
+        )
+}
+
+/// Agrega `value` al header `Vary` si no estaba
+pub fn add_vary(res: &mut Response, value: &str) {
+    let vary = match res.headers.get("Vary") {
+        Some(v) if v.split(',').any(|x| x.trim().eq_ignore_ascii_case(value)) => return,
+        Some(v) => format!("{}, {}", v, value),
+        None => value.to_string(),
+    };
+    res.headers.remove("Vary");
+    res.headers.insert("Vary".to_string(), vary);
+}
+
+impl Middleware for Compression {
+    fn after(&self, req: &Request, res: &mut Response) {
+        if !res
+            .headers
+            .get("Content-Type")
+            .is_some_and(|ct| is_compressible(ct))
+        {
+            return;
+        }
+        // La response depende de Accept-Encoding aunque esta vez no se comprima
+        add_vary(res, "Accept-Encoding");
+
+        if !res.status.is_success()
+            || res.status == StatusCode::NOCONTENT
+            || res.status == StatusCode::PARTIALCONTENT
+            || res.headers.get("Content-Encoding").is_some()
+            || !res
+                .body
+                .len()
+                .is_some_and(|len| len >= self.min_size && len <= self.max_size)
+        {
+            return;
+        }
+        let encoding = match req
+            .headers
+            .get("Accept-Encoding")
+            .and_then(|accept| negotiate_encoding(accept, &["gzip", "deflate"]))
+        {
+            Some(enc) => enc,
+            None => return,
+        };
+
+        let data = match std::mem::take(&mut res.body).into_bytes() {
+            Ok(data) => data,
+            Err(_) => {
+                *res = Response::internal_err("Could not read response body");
+                return;
+            }
+        };
+        let compressed = match encoding {
+            "gzip" => gzip(&data),
+            _ => zlib(&data),
+        };
+        // Si comprimir no reduce el tamaño se envía el original
+        if compressed.len() >= data.len() {
+            res.body = data.into();
+            return;
+        }
+        res.body = compressed.into();
+        res.headers.remove("Content-Length");
+        res.headers
+            .insert("Content-Encoding".to_string(), encoding.to_string());
+        // El cuerpo comprimido es otra representación: su ETag solo puede ser débil
+        if let Some(etag) = res.headers.get("ETag") {
+            if !etag.starts_with("W/") {
+                let weak = format!("W/{}", etag);
+                res.headers.insert("ETag".to_string(), weak);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{Body, Headers, Method, StatusCode};
+
+    struct Auth;
+
+    impl Middleware for Auth {
+        fn before(&self, req: &mut Request) -> Option<Response> {
+            match req.headers.get("Authorization") {
+                Some(_) => None,
+                None => Some(Response::internal_err("unauthorized")),
+            }
+        }
+    }
+
+    struct Tag(&'static str);
+
+    impl Middleware for Tag {
+        fn before(&self, req: &mut Request) -> Option<Response> {
+            req.body.push_str(self.0);
+            None
+        }
+
+        fn after(&self, _req: &Request, res: &mut Response) {
+            if let Body::Bytes(bytes) = &mut res.body {
+                bytes.extend_from_slice(self.0.as_bytes());
+            }
+        }
+    }
+
+    fn request(headers: Vec<(&str, &str)>) -> Request {
+        Request {
+            headers: Headers::from(&headers),
+            ..Request::new(Method::GET, "/")
+        }
+    }
+
+    fn echo(req: &mut Request) -> Response {
+        Response::ok(&format!("[{}]", req.body))
+    }
+
+    #[test]
+    // prueba del orden de ejecución de before/after en la cadena
+    fn test_chain_order() {
+        let chain: Vec<Arc<dyn Middleware>> = vec![Arc::new(Tag("a")), Arc::new(Tag("b"))];
+        let res = Next::new(&chain, &echo).run(&mut request(vec![]));
+        assert_eq!(res.body, b"[ab]ba");
+    }
+
+    #[test]
+    // prueba de un middleware que corta la cadena
+    fn test_short_circuit() {
+        let chain: Vec<Arc<dyn Middleware>> = vec![Arc::new(Auth), Arc::new(Tag("a"))];
+
+        let res = Next::new(&chain, &echo).run(&mut request(vec![]));
+        assert_eq!(res.status, StatusCode::INTERNALERR);
+        assert_eq!(res.body, b"unauthorized");
+
+        let res = Next::new(&chain, &echo).run(&mut request(vec![("Authorization", "x")]));
+        assert_eq!(res.body, b"[a]a");
+    }
+
+    #[test]
+    // prueba del middleware de cookies
+    fn test_cookie_echo() {
+        let chain: Vec<Arc<dyn Middleware>> = vec![Arc::new(CookieEcho)];
+        let res = Next::new(&chain, &echo).run(&mut request(vec![("Cookie", "session=abc")]));
+        assert_eq!(
+            res.headers.get("Set-Cookie"),
+            Some(&"session=abc".to_string())
+        );
+
+        let res = Next::new(&chain, &echo).run(&mut request(vec![]));
+        assert_eq!(res.headers.get("Set-Cookie"), None);
+    }
+
+    #[test]
+    // prueba de compresión negociada con Accept-Encoding
+    fn test_compression() {
+        let html = "<p>hola mundo</p>\n".repeat(100);
+        let endpoint = |req: &mut Request| {
+            let content_type = if req.path.as_str().ends_with(".png") {
+                "image/png"
+            } else {
+                "text/html"
+            };
+            Response::builder()
+                .header("Content-Type", content_type)
+                .body("<p>hola mundo</p>\n".repeat(100))
+        };
+        let chain: Vec<Arc<dyn Middleware>> = vec![Arc::new(Compression::new())];
+
+        let mut req = request(vec![("Accept-Encoding", "deflate;q=0.5, gzip")]);
+        let res = Next::new(&chain, &endpoint).run(&mut req);
+        assert_eq!(
+            res.headers.get("Content-Encoding"),
+            Some(&"gzip".to_string())
+        );
+        assert_eq!(
+            res.headers.get("Vary"),
+            Some(&"Accept-Encoding".to_string())
+        );
+        let body = res.body.into_bytes().unwrap();
+        assert!(body.len() < html.len());
+        assert_eq!(&body[..2], &[0x1F, 0x8B]);
+
+        // sin Accept-Encoding se envía tal cual, pero con Vary
+        let res = Next::new(&chain, &endpoint).run(&mut request(vec![]));
+        assert!(res.headers.get("Content-Encoding").is_none());
+        assert_eq!(
+            res.headers.get("Vary"),
+            Some(&"Accept-Encoding".to_string())
+        );
+        assert_eq!(res.body, html);
+
+        // los formatos ya comprimidos no se tocan
+        let mut req = request(vec![("Accept-Encoding", "gzip")]);
+        req.path = "/foto.png".into();
+        let res = Next::new(&chain, &endpoint).run(&mut req);
+        assert!(res.headers.get("Content-Encoding").is_none());
+        assert!(res.headers.get("Vary").is_none());
+
+        // por debajo del umbral no se comprime
+        let chain: Vec<Arc<dyn Middleware>> = vec![Arc::new(Compression::new().min_size(1 << 20))];
+        let mut req = request(vec![("Accept-Encoding", "gzip")]);
+        let res = Next::new(&chain, &endpoint).run(&mut req);
+        assert!(res.headers.get("Content-Encoding").is_none());
+    }
+
+    #[test]
+    // prueba de tipos comprimibles y del header Vary
+    fn test_compressible_and_vary() {
+        assert!(is_compressible("text/html; charset=utf-8"));
+        assert!(is_compressible("application/json"));
+        assert!(is_compressible("image/svg+xml"));
+        assert!(!is_compressible("image/png"));
+        assert!(!is_compressible("image/jpeg"));
+        assert!(!is_compressible("image/webp"));
+        assert!(!is_compressible("application/octet-stream"));
+
+        let mut res = Response::ok("x");
+        add_vary(&mut res, "Accept-Encoding");
+        add_vary(&mut res, "accept-encoding");
+        add_vary(&mut res, "Origin");
+        assert_eq!(
+            res.headers.get("Vary"),
+            Some(&"Accept-Encoding, Origin".to_string())
+        );
+    }
+}
@@ -1,25 +1,45 @@
+pub use body::Body;
+pub use cache_control::{CacheMatch, CachePolicy, CacheRule};
+pub use error::{ErrorFormat, HttpError, IntoResponse};
+pub use file_cache::{CacheStats, CachedBytes, FileCache};
 pub use headers::Headers;
 pub use method::Method;
+pub use middleware::{Compression, CookieEcho, Logger, Middleware, Next};
 pub use mime_type::mime_type;
 pub use path::Path;
-pub use request::Request;
-pub use response::Response;
-use router::{Callback, Router};
+pub use request::{Request, Version};
+pub use response::{Response, ResponseBuilder};
+pub use router::{RouteInfo, RouteKind, Router, TrailingSlash};
+pub use rules::{Rule, RuleAction, Rules};
 use serve::{serve, StreamType};
 pub use server::Server;
 pub use status_code::StatusCode;
+pub use template::{Context, Template, TemplateError, Templates, Value};
 use traits::{ReadFrom, WriteTo};
 pub use utils::parse_url_param;
 
+mod body;
+mod cache_control;
+mod conditional;
+mod date;
+mod deflate;
+mod error;
+mod file_cache;
+mod files;
 mod headers;
 mod method;
+mod middleware;
 mod mime_type;
 mod path;
+mod range;
 mod request;
 mod response;
 mod router;
+mod rules;
+mod sendfile;
 mod serve;
 mod server;
 mod status_code;
+mod template;
 mod traits;
 mod utils;
\ No newline at end of file
@@ -4,14 +4,59 @@ use std::convert::From;
 use std::fmt;
 
 // Estructura que representa un Path (ruta principal y parámetros)
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 
 pub struct Path {
     data: String,
     params: Option<String>,
+    // Prefijo de montaje ya consumido por un Router padre
+    base: String,
 }
 
 impl Path {
+    // Prefijo bajo el cual se montó el Router que atiende la request
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    // Ruta relativa al Router actual, sin parámetros
+    pub fn as_str(&self) -> &str {
+        &self.data
+    }
+
+    // Quita un prefijo de montaje, moviéndolo a `base`
+    // Solo coincide en límites de segmento: "/api" coincide con "/api/x" pero no con "/apix"
+    pub fn strip_prefix(&self, prefix: &str) -> Option<Path> {
+        let prefix = prefix.trim_end_matches('/');
+        let rest = self.data.strip_prefix(prefix)?;
+        let rest = match rest {
+            "" => "/",
+            r if r.starts_with('/') => r,
+            _ => return None,
+        };
+        Some(Path {
+            data: rest.to_string(),
+            params: self.params.clone(),
+            base: format!("{}{}", self.base, prefix),
+        })
+    }
+
+    // Agrega o quita la '/' final de la ruta; la raíz no tiene alternativa
+    pub fn toggle_trailing_slash(&self) -> Option<Path> {
+        if self.data == "/" {
+            return None;
+        }
+        let data = match self.data.strip_suffix('/') {
+            Some(d) => d.to_string(),
+            None => format!("{}/", self.data),
+        };
+        Some(Path {
+            data,
+            params: self.params.clone(),
+            base: self.base.clone(),
+        })
+    }
+
     // Convertir parámetros en un HashMap
     pub fn parse_params(&self) -> Result<HashMap<&str, &str>, &'static str> {
         // Si hay parámetros, los analizamos, sino devolvemos un HashMap vacío
@@ -29,10 +74,70 @@ impl From<&str> for Path {
             (d.to_string(), Some(p.to_string()))
         });
 
-        Path { data, params }
+        Path {
+            data: normalize(&data),
+            params,
+            base: String::new(),
+        }
     }
 }
 
+// Canoniza una ruta: decodifica los caracteres no reservados, elimina los
+// segmentos "." y ".." (RFC 3986, sección 5.2.4) y colapsa las '/' repetidas.
+// La '/' final se conserva porque forma parte de la política de cada Router
+fn normalize(raw: &str) -> String {
+    let decoded = decode_unreserved(raw);
+
+    let mut segments: Vec<&str> = Vec::new();
+    let mut trailing = decoded.ends_with('/');
+    for segment in decoded.split('/') {
+        match segment {
+            "" => continue,
+            "." => trailing = true,
+            ".." => {
+                segments.pop();
+                trailing = true;
+            }
+            s => {
+                segments.push(s);
+                trailing = false;
+            }
+        }
+    }
+    trailing |= decoded.ends_with('/');
+
+    let mut path = format!("/{}", segments.join("/"));
+    if trailing && !segments.is_empty() {
+        path.push('/');
+    }
+    path
+}
+
+// Decodifica solo las secuencias %XX de caracteres no reservados (letras, dígitos,
+// '-', '.', '_', '~'); el resto se deja codificado, normalizado a mayúsculas
+fn decode_unreserved(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(i) = rest.find('%') {
+        out.push_str(&rest[..i]);
+        let hex = rest
+            .get(i + 1..i + 3)
+            .filter(|h| h.bytes().all(|b| b.is_ascii_hexdigit()));
+        match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+            Some(b) if b.is_ascii_alphanumeric() || b"-._~".contains(&b) => out.push(b as char),
+            Some(_) => out.push_str(&rest[i..i + 3].to_ascii_uppercase()),
+            None => {
+                out.push('%');
+                rest = &rest[i + 1..];
+                continue;
+            }
+        }
+        rest = &rest[i + 3..];
+    }
+    out.push_str(rest);
+    out
+}
+
 // Comparar Path con un string
 impl std::cmp::PartialEq<String> for Path {
     fn eq(&self, other: &String) -> bool {
@@ -45,8 +150,8 @@ impl fmt::Display for Path {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // Si hay parámetros, los incluimos en la salida, sino solo la ruta principal
         match &self.params {
-            Some(p) => write!(f, "{}?{}", &self.data, p),
-            None => write!(f, "{}", &self.data),
+            Some(p) => write!(f, "{}{}?{}", &self.base, &self.data, p),
+            None => write!(f, "{}{}", &self.base, &self.data),
         }
     }
 }
@@ -100,4 +205,51 @@ mod tests {
         // Use assert! instead of assert_eq! to avoid the need for Debug
         assert!(path == other, "Expected path to be equal to {}", other);
     }
+
+    //prueba que se pueda quitar un prefijo de montaje
+    #[test]
+    fn test_strip_prefix() {
+        let path = Path::from("/api/users?id=1");
+
+        let inner = path.strip_prefix("/api/").unwrap();
+        assert_eq!(inner.as_str(), "/users");
+        assert_eq!(inner.base(), "/api");
+        assert_eq!(inner.to_string(), "/api/users?id=1");
+
+        assert_eq!(
+            Path::from("/api").strip_prefix("/api").unwrap().as_str(),
+            "/"
+        );
+        assert!(Path::from("/apix").strip_prefix("/api").is_none());
+        assert!(Path::from("/other").strip_prefix("/api").is_none());
+    }
+
+    //prueba de canonización de rutas
+    #[test]
+    fn test_normalize() {
+        assert_eq!(Path::from("/index.html").as_str(), "/index.html");
+        assert_eq!(Path::from("//index.html").as_str(), "/index.html");
+        assert_eq!(Path::from("/./index.html").as_str(), "/index.html");
+        assert_eq!(Path::from("/a/b/../c/./d.html").as_str(), "/a/c/d.html");
+        assert_eq!(Path::from("/../../etc/passwd").as_str(), "/etc/passwd");
+        assert_eq!(Path::from("/%7Euser/%61bc").as_str(), "/~user/abc");
+        assert_eq!(Path::from("/a/%2e%2E/b").as_str(), "/b");
+        assert_eq!(Path::from("/a%2fb%20c").as_str(), "/a%2Fb%20c");
+        assert_eq!(Path::from("/100%").as_str(), "/100%");
+        assert_eq!(Path::from("/login/").as_str(), "/login/");
+        assert_eq!(Path::from("/login/.").as_str(), "/login/");
+        assert_eq!(Path::from("/login//").as_str(), "/login/");
+        assert_eq!(Path::from("").as_str(), "/");
+        assert_eq!(Path::from("/..").as_str(), "/");
+    }
+
+    //prueba de alternar la '/' final
+    #[test]
+    fn test_toggle_trailing_slash() {
+        let path = Path::from("/login?next=home");
+        let toggled = path.toggle_trailing_slash().unwrap();
+        assert_eq!(toggled.to_string(), "/login/?next=home");
+        assert_eq!(toggled.toggle_trailing_slash().unwrap().as_str(), "/login");
+        assert!(Path::from("/").toggle_trailing_slash().is_none());
+    }
 }
@@ -0,0 +1,342 @@
+use super::date::parse_http_date;
+use super::{Body, Method, Request, Response, StatusCode};
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Máximo de rangos aceptados en una request; más que eso se ignora el header
+const MAX_RANGES: usize = 16;
+
+/// Resultado de interpretar un header `Range` contra un recurso de `len` bytes
+#[derive(Debug, PartialEq)]
+pub enum RangeSpec {
+    /// Header ausente, con otra unidad o mal formado: se envía el recurso completo
+    Full,
+    /// Rangos inclusivos `(inicio, fin)` a enviar con 206
+    Partial(Vec<(u64, u64)>),
+    /// Ningún rango cae dentro del recurso: 416
+    Unsatisfiable,
+}
+
+/// Interpreta `Range: bytes=...` (RFC 9110 14.1.2): rangos `a-b`, abiertos `a-` y sufijos `-n`
+pub fn parse_range(header: &str, len: u64) -> RangeSpec {
+    let specs = match header.trim().strip_prefix("bytes=") {
+        Some(s) => s,
+        None => return RangeSpec::Full,
+    };
+
+    let mut ranges = Vec::new();
+    let mut count = 0;
+    for spec in specs.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        count += 1;
+        if count > MAX_RANGES {
+            return RangeSpec::Full;
+        }
+        let (start, end) = match spec.split_once('-') {
+            Some(pair) => pair,
+            None => return RangeSpec::Full,
+        };
+        let range = match (start.trim(), end.trim()) {
+            // Sufijo: los últimos n bytes
+            ("", n) => match n.parse::<u64>() {
+                Ok(0) => None,
+                Ok(n) if len > 0 => Some((len.saturating_sub(n), len - 1)),
+                Ok(_) => None,
+                Err(_) => return RangeSpec::Full,
+            },
+            (a, b) => {
+                let a = match a.parse::<u64>() {
+                    Ok(a) => a,
+                    Err(_) => return RangeSpec::Full,
+                };
+                let b = match b {
+                    "" => u64::MAX,
+                    b => match b.parse::<u64>() {
+                        Ok(b) if b >= a => b,
+                        _ => return RangeSpec::Full,
+                    },
+                };
+                (a < len).then(|| (a, b.min(len - 1)))
+            }
+        };
+        ranges.extend(range);
+    }
+
+    match (count, ranges.is_empty()) {
+        (0, _) => RangeSpec::Full,
+        (_, true) => RangeSpec::Unsatisfiable,
+        _ => RangeSpec::Partial(ranges),
+    }
+}
+
+// If-Range solo permite el rango si el validador coincide con el actual y es fuerte:
+// un ETag fuerte idéntico o la misma fecha de Last-Modified, que solo cuenta como
+// fuerte si pasó al menos un segundo (su resolución) desde esa fecha
+fn if_range_matches(req: &Request, res: &Response) -> bool {
+    let value = match req.headers.get("If-Range") {
+        Some(v) => v.trim(),
+        None => return true,
+    };
+    if value.starts_with('"') {
+        return res.headers.get("ETag").is_some_and(|etag| etag == value);
+    }
+    !value.starts_with("W/")
+        && res
+            .headers
+            .get("Last-Modified")
+            .is_some_and(|lm| lm == value)
+        && parse_http_date(value).is_some_and(|modified| {
+            SystemTime::now()
+                .duration_since(modified)
+                .is_ok_and(|age| age >= Duration::from_secs(1))
+        })
+}
+
+/// Aplica el header `Range` de la request a la response completa (200) del archivo `path`.
+/// Devuelve 206 con uno o varios rangos (`multipart/byteranges`), 416 si no hay rangos
+/// válidos, o la response original si no corresponde enviar rangos
+pub fn apply_range(req: &Request, path: &Path, mut res: Response) -> Response {
+    if res.status != StatusCode::OK {
+        return res;
+    }
+    res.headers
+        .insert("Accept-Ranges".to_string(), "bytes".to_string());
+
+    let len = match (req.method == Method::GET, res.body.len()) {
+        (true, Some(len)) => len,
+        _ => return res,
+    };
+    let ranges = match req.headers.get("Range").map(|r| parse_range(r, len)) {
+        None | Some(RangeSpec::Full) => return res,
+        Some(_) if !if_range_matches(req, &res) => return res,
+        Some(RangeSpec::Unsatisfiable) => {
+            let mut err = Response::builder()
+                .status(StatusCode::RANGENOTSATISFIABLE)
+                .header("Content-Type", "text/plain")
+                .header("Content-Range", &format!("bytes */{}", len))
+                .body("416\n");
+            err.headers
+                .insert("Accept-Ranges".to_string(), "bytes".to_string());
+            return err;
+        }
+        Some(RangeSpec::Partial(ranges)) => ranges,
+    };
+
+    match partial_body(path, &ranges, len, &res) {
+        Ok((body, content_type)) => {
+            res.status = StatusCode::PARTIALCONTENT;
+            res.body = body;
+            res.headers.remove("Content-Length");
+            if let [(start, end)] = ranges[..] {
+                res.headers.insert(
+                    "Content-Range".to_string(),
+                    format!("bytes {}-{}/{}", start, end, len),
+                );
+            }
+            if let Some(content_type) = content_type {
+                res.headers.remove("Content-Type");
+                res.headers.insert("Content-Type".to_string(), content_type);
+            }
+            res
+        }
+        Err(_) => Response::internal_err("Could not read file"),
+    }
+}
+
+// Arma el cuerpo con los rangos pedidos, leyendo el archivo sin cargarlo en memoria.
+// Con varios rangos devuelve también el Content-Type multipart con su boundary
+fn partial_body(
+    path: &Path,
+    ranges: &[(u64, u64)],
+    len: u64,
+    res: &Response,
+) -> std::io::Result<(Body, Option<String>)> {
+    let section = |start: u64, end: u64| -> std::io::Result<Box<dyn Read + Send>> {
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(start))?;
+        Ok(Box::new(file.take(end - start + 1)))
+    };
+
+    if let [(start, end)] = ranges[..] {
+        let body = Body::file_part(File::open(path)?, start, end - start + 1)?;
+        return Ok((body, None));
+    }
+
+    let boundary = format!(
+        "{:016x}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64)
+            ^ len
+    );
+    let part_type = res
+        .headers
+        .get("Content-Type")
+        .map_or(String::new(), |ct| format!("Content-Type: {}\r\n", ct));
+
+    let mut reader: Box<dyn Read + Send> = Box::new(Cursor::new(Vec::new()));
+    let mut total = 0;
+    for (i, &(start, end)) in ranges.iter().enumerate() {
+        let head = format!(
+            "{}--{}\r\n{}Content-Range: bytes {}-{}/{}\r\n\r\n",
+            if i == 0 { "" } else { "\r\n" },
+            boundary,
+            part_type,
+            start,
+            end,
+            len
+        );
+        total += head.len() as u64 + end - start + 1;
+        reader = Box::new(reader.chain(Cursor::new(head)).chain(section(start, end)?));
+    }
+    let tail = format!("\r\n--{}--\r\n", boundary);
+    total += tail.len() as u64;
+    reader = Box::new(reader.chain(Cursor::new(tail)));
+
+    Ok((
+        Body::reader(reader, Some(total)),
+        Some(format!("multipart/byteranges; boundary={}", boundary)),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::date::fmt_http_date;
+    use crate::http::Headers;
+    use std::env;
+    use std::fs;
+
+    fn request(headers: Vec<(&str, &str)>) -> Request {
+        Request {
+            headers: Headers::from(&headers),
+            ..Request::new(Method::GET, "/data.txt")
+        }
+    }
+
+    #[test]
+    // prueba de interpretación del header Range
+    fn test_parse_range() {
+        assert_eq!(
+            parse_range("bytes=0-4", 10),
+            RangeSpec::Partial(vec![(0, 4)])
+        );
+        assert_eq!(
+            parse_range("bytes=5-", 10),
+            RangeSpec::Partial(vec![(5, 9)])
+        );
+        assert_eq!(
+            parse_range("bytes=-3", 10),
+            RangeSpec::Partial(vec![(7, 9)])
+        );
+        assert_eq!(
+            parse_range("bytes=-30", 10),
+            RangeSpec::Partial(vec![(0, 9)])
+        );
+        assert_eq!(
+            parse_range("bytes=0-0, 8-20", 10),
+            RangeSpec::Partial(vec![(0, 0), (8, 9)])
+        );
+        // los rangos fuera del recurso se descartan
+        assert_eq!(
+            parse_range("bytes=20-30, 1-2", 10),
+            RangeSpec::Partial(vec![(1, 2)])
+        );
+        assert_eq!(parse_range("bytes=10-", 10), RangeSpec::Unsatisfiable);
+        assert_eq!(parse_range("bytes=-0", 10), RangeSpec::Unsatisfiable);
+        assert_eq!(parse_range("bytes=0-", 0), RangeSpec::Unsatisfiable);
+        // errores de sintaxis u otras unidades se ignoran
+        assert_eq!(parse_range("bytes=5-2", 10), RangeSpec::Full);
+        assert_eq!(parse_range("bytes=a-b", 10), RangeSpec::Full);
+        assert_eq!(parse_range("items=0-1", 10), RangeSpec::Full);
+        assert_eq!(parse_range("bytes=", 10), RangeSpec::Full);
+        assert_eq!(
+            parse_range(&format!("bytes={}", "0-0,".repeat(17)), 10),
+            RangeSpec::Full
+        );
+    }
+
+    #[test]
+    // prueba de respuestas 206, multipart/byteranges, 416 e If-Range
+    fn test_apply_range() {
+        let path = env::temp_dir().join(format!("range-{}.txt", std::process::id()));
+        fs::write(&path, "0123456789").unwrap();
+        let serve = |headers: Vec<(&str, &str)>| {
+            let mut res = Response::file(&path);
+            res.headers.insert("ETag".to_string(), "\"v1\"".to_string());
+            apply_range(&request(headers), &path, res)
+        };
+
+        let res = serve(vec![]);
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.headers.get("Accept-Ranges"), Some(&"bytes".to_string()));
+
+        let res = serve(vec![("Range", "bytes=2-5")]);
+        assert_eq!(res.status, StatusCode::PARTIALCONTENT);
+        assert_eq!(
+            res.headers.get("Content-Range"),
+            Some(&"bytes 2-5/10".to_string())
+        );
+        assert_eq!(res.body.len(), Some(4));
+        assert_eq!(res.body.into_bytes().unwrap(), b"2345");
+
+        let res = serve(vec![("Range", "bytes=0-1,-2")]);
+        assert_eq!(res.status, StatusCode::PARTIALCONTENT);
+        let content_type = res.headers.get("Content-Type").unwrap().clone();
+        let boundary = content_type
+            .strip_prefix("multipart/byteranges; boundary=")
+            .unwrap();
+        let len = res.body.len().unwrap();
+        let body = String::from_utf8(res.body.into_bytes().unwrap()).unwrap();
+        assert_eq!(body.len() as u64, len);
+        assert_eq!(
+            body,
+            format!(
+                "--{b}\r\nContent-Type: text/plain\r\nContent-Range: bytes 0-1/10\r\n\r\n01\
+                 \r\n--{b}\r\nContent-Type: text/plain\r\nContent-Range: bytes 8-9/10\r\n\r\n89\
+                 \r\n--{b}--\r\n",
+                b = boundary
+            )
+        );
+
+        let res = serve(vec![("Range", "bytes=50-")]);
+        assert_eq!(res.status, StatusCode::RANGENOTSATISFIABLE);
+        assert_eq!(
+            res.headers.get("Content-Range"),
+            Some(&"bytes */10".to_string())
+        );
+
+        // If-Range con el ETag actual permite el rango; con otro, se envía todo
+        let res = serve(vec![("Range", "bytes=0-0"), ("If-Range", "\"v1\"")]);
+        assert_eq!(res.status, StatusCode::PARTIALCONTENT);
+        let res = serve(vec![("Range", "bytes=0-0"), ("If-Range", "\"v0\"")]);
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.body.len(), Some(10));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    // prueba de If-Range con fecha: solo vale si Last-Modified es un validador fuerte
+    fn test_if_range_date() {
+        let path = env::temp_dir().join(format!("range-date-{}.txt", std::process::id()));
+        fs::write(&path, "0123456789").unwrap();
+        let serve = |modified: SystemTime| {
+            let date = fmt_http_date(modified);
+            let mut res = Response::file(&path);
+            res.headers
+                .insert("Last-Modified".to_string(), date.clone());
+            let req = request(vec![("Range", "bytes=0-0"), ("If-Range", &date)]);
+            apply_range(&req, &path, res).status
+        };
+
+        // modificado hace más de un segundo: la fecha identifica el contenido
+        let old = SystemTime::now() - Duration::from_secs(60);
+        assert_eq!(serve(old), StatusCode::PARTIALCONTENT);
+        // modificado en este mismo segundo: podría cambiar sin que cambie la fecha
+        assert_eq!(serve(SystemTime::now()), StatusCode::OK);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,15 +1,66 @@
 use super::{Headers, Method, Path, ReadFrom};
 use std::convert::TryFrom;
+use std::fmt;
 use std::io::BufRead;
+use std::net::SocketAddr;
+
+/// Versión del protocolo indicada en la línea de solicitud
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Version {
+    Http10,
+    #[default]
+    Http11,
+}
+
+impl From<&str> for Version {
+    // Las versiones desconocidas o ausentes se tratan como HTTP/1.0
+    fn from(s: &str) -> Self {
+        match s.trim() {
+            "HTTP/1.1" => Version::Http11,
+            _ => Version::Http10,
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Version::Http10 => write!(f, "HTTP/1.0"),
+            Version::Http11 => write!(f, "HTTP/1.1"),
+        }
+    }
+}
 
 /// Solicitud HTTP
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 
 pub struct Request {
     pub method: Method,
     pub path: Path,
+    pub version: Version,
     pub headers: Headers,
     pub body: String,
+    pub peer_addr: Option<SocketAddr>,
+}
+
+impl Request {
+    /// Crea una request HTTP/1.1 sin headers ni cuerpo
+    pub fn new(method: Method, path: &str) -> Self {
+        Self {
+            method,
+            path: Path::from(path),
+            version: Version::Http11,
+            headers: Headers::from(&vec![]),
+            body: String::new(),
+            peer_addr: None,
+        }
+    }
+}
+
+impl Default for Request {
+    fn default() -> Self {
+        Self::new(Method::GET, "/")
+    }
 }
 
 impl ReadFrom for Request {
@@ -46,6 +97,7 @@ impl ReadFrom for Request {
                 .next()
                 .ok_or("First line doesn't have a path")?,
         );
+        let version = Version::from(first_line_splitted.next().unwrap_or_default());
 
         // Analiza los encabezados
         let headers =
@@ -57,8 +109,10 @@ impl ReadFrom for Request {
         Ok(Self {
             method,
             path,
+            version,
             headers,
             body,
+            peer_addr: None,
         })
     }
 }
@@ -88,6 +142,7 @@ mod tests {
 
         assert_eq!(request.method, Method::GET, "El método falló");
         assert_eq!(request.path.to_string(), "/index.html", "La ruta falló");
+        assert_eq!(request.version, Version::Http11, "La versión falló");
         assert_eq!(
             request.headers.get("Host"),
             Some(&"www.example.com".to_string()),
@@ -103,4 +158,29 @@ mod tests {
             "El cuerpo de la solicitud falló"
         );
     }
+
+    #[test]
+    // Prueba el constructor de requests
+    fn test_request_new() {
+        let request = Request::new(Method::POST, "/login?next=/");
+        assert_eq!(request.method, Method::POST);
+        assert_eq!(request.path.as_str(), "/login");
+        assert_eq!(request.version, Version::Http11);
+        assert!(request.body.is_empty());
+        assert_eq!(Request::default().path.to_string(), "/");
+    }
+
+    #[test]
+    // Prueba la lectura de la versión del protocolo
+    fn test_request_version() {
+        let mut cursor = Cursor::new("GET / HTTP/1.0\r\n\r\n");
+        let request = Request::read_from(&mut cursor).unwrap();
+        assert_eq!(request.version, Version::Http10);
+        assert_eq!(request.version.to_string(), "HTTP/1.0");
+
+        // Sin versión (HTTP/0.9) no se asume soporte de HTTP/1.1
+        let mut cursor = Cursor::new("GET /\r\n\r\n");
+        let request = Request::read_from(&mut cursor).unwrap();
+        assert_eq!(request.version, Version::Http10);
+    }
 }
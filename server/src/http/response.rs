@@ -1,8 +1,28 @@
-use super::{mime_type, Headers, StatusCode, WriteTo};
+use super::conditional::{file_validators, is_not_modified, not_modified};
+use super::date::fmt_http_date;
+use super::range::apply_range;
+use super::sendfile::send_file;
+use super::utils::negotiate_encoding;
+use super::{
+    mime_type, Body, Context, FileCache, Headers, HttpError, Request, StatusCode, Template, WriteTo,
+};
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::fs::{self, File};
+use std::io::{self, Cursor, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// Codificaciones precomprimidas que se buscan junto a los archivos, en orden de preferencia
+const PRECOMPRESSED: [(&str, &str); 2] = [("br", "br"), ("gzip", "gz")];
+
+// Ruta del archivo hermano con la extensión `ext` agregada: app.js -> app.js.gz
+fn sibling(path: &Path, ext: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(ext);
+    PathBuf::from(name)
+}
 
 /// Respuesta HTTP
 #[derive(Debug)]
@@ -10,16 +30,142 @@ use std::path::PathBuf;
 pub struct Response {
     pub status: StatusCode,
     pub headers: Headers,
-    pub body: Vec<u8>,
+    pub body: Body,
+    /// Headers enviados después del cuerpo; solo se transmiten con `Transfer-Encoding: chunked`
+    pub trailers: Headers,
+    /// Error del manejador que originó esta response, si lo hubo
+    pub error: Option<HttpError>,
+    // La armó un manejador; los manejadores de error del servidor no la reemplazan
+    pub(crate) from_handler: bool,
+}
+
+/// Constructor incremental de una `Response`
+pub struct ResponseBuilder {
+    status: StatusCode,
+    headers: Headers,
+}
+
+impl ResponseBuilder {
+    /// Código de estado de la response (200 por defecto)
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Agrega o reemplaza un header
+    pub fn header(mut self, key: &str, value: &str) -> Self {
+        self.headers.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Termina la response con el cuerpo dado
+    pub fn body<B: Into<Body>>(self, body: B) -> Response {
+        Response {
+            status: self.status,
+            headers: self.headers,
+            body: body.into(),
+            trailers: Headers::from(&vec![]),
+            error: None,
+            from_handler: false,
+        }
+    }
 }
 
 impl Response {
+    /// Crea un constructor incremental: `Response::builder().status(..).header(..).body(..)`
+    pub fn builder() -> ResponseBuilder {
+        ResponseBuilder {
+            status: StatusCode::OK,
+            headers: Headers::from(&vec![]),
+        }
+    }
+
+    // Response de texto plano con el código dado
+    fn text(status: StatusCode, body: &str) -> Self {
+        Self::builder()
+            .status(status)
+            .header("Content-Type", "text/plain")
+            .body(body)
+    }
+
+    /// Respuesta 201 Created con la ubicación del recurso creado
+    pub fn created(location: &str, body: &str) -> Self {
+        let mut res = Self::text(StatusCode::CREATED, body);
+        res.headers
+            .insert("Location".to_string(), location.to_string());
+        res
+    }
+
+    /// Respuesta 204 No Content
+    pub fn no_content() -> Self {
+        Self::builder().status(StatusCode::NOCONTENT).body("")
+    }
+
+    /// Respuesta 400 Bad Request
+    pub fn bad_request(body: &str) -> Self {
+        Self::text(StatusCode::BADREQUEST, body)
+    }
+
+    /// Respuesta 401 Unauthorized que pide credenciales Basic para `realm`
+    pub fn unauthorized(realm: &str) -> Self {
+        let mut res = Self::text(StatusCode::UNAUTHORIZED, "Unauthorized");
+        res.headers.insert(
+            "WWW-Authenticate".to_string(),
+            format!("Basic realm=\"{}\"", realm.replace('"', "")),
+        );
+        res
+    }
+
+    /// Respuesta 403 Forbidden
+    pub fn forbidden(body: &str) -> Self {
+        Self::text(StatusCode::FORBIDDEN, body)
+    }
+
+    /// Respuesta 409 Conflict
+    pub fn conflict(body: &str) -> Self {
+        Self::text(StatusCode::CONFLICT, body)
+    }
+
+    /// Respuesta 422 Unprocessable Content
+    pub fn unprocessable(body: &str) -> Self {
+        Self::text(StatusCode::UNPROCESSABLE, body)
+    }
+
+    /// Respuesta 413 Payload Too Large
+    pub fn payload_too_large() -> Self {
+        Self::text(StatusCode::PAYLOADTOOLARGE, "Payload Too Large")
+    }
+
+    /// Respuesta 429 Too Many Requests; el cliente puede reintentar tras `retry_after` segundos
+    pub fn too_many_requests(retry_after: u64) -> Self {
+        let mut res = Self::text(StatusCode::TOOMANYREQUESTS, "Too Many Requests");
+        res.headers
+            .insert("Retry-After".to_string(), retry_after.to_string());
+        res
+    }
+
+    /// Respuesta 503 Service Unavailable; el cliente puede reintentar tras `retry_after` segundos
+    pub fn unavailable(retry_after: u64) -> Self {
+        let mut res = Self::text(StatusCode::UNAVAILABLE, "Service Unavailable");
+        res.headers
+            .insert("Retry-After".to_string(), retry_after.to_string());
+        res
+    }
+
     /// Respuesta de redirección
     pub fn redirect(path: &str) -> Self {
+        Self::redirect_with(path, StatusCode::REDIRECT)
+    }
+
+    /// Respuesta de redirección con un código específico (301, 302, 303, 307 o 308)
+    pub fn redirect_with(path: &str, status: StatusCode) -> Self {
         Self {
-            status: StatusCode::REDIRECT,
+            status,
             headers: Headers::from(&vec![("Content-Type", "text/plain"), ("Location", path)]),
-            body: vec![],
+            body: Body::empty(),
+            trailers: Headers::from(&vec![]),
+            error: None,
+            from_handler: false,
         }
     }
 
@@ -28,16 +174,29 @@ impl Response {
         Self {
             status: StatusCode::OK,
             headers: Headers::from(&vec![("Content-Type", "text/plain")]),
-            body: body.bytes().collect(),
+            body: body.into(),
+            trailers: Headers::from(&vec![]),
+            error: None,
+            from_handler: false,
         }
     }
 
+    /// Respuesta 200 con el HTML de `template` renderizado con las variables de `context`
+    pub fn render(template: &Template, context: &Context) -> Self {
+        Self::builder()
+            .header("Content-Type", "text/html; charset=utf-8")
+            .body(template.render(context))
+    }
+
     /// Respuesta 404 Not Found
     pub fn not_found() -> Self {
         Self {
             status: StatusCode::NOTFOUND,
             headers: Headers::from(&vec![("Content-Type", "text/plain")]),
-            body: "404\n".bytes().collect(),
+            body: "404\n".into(),
+            trailers: Headers::from(&vec![]),
+            error: None,
+            from_handler: false,
         }
     }
 
@@ -46,26 +205,148 @@ impl Response {
         Response {
             status: StatusCode::INTERNALERR,
             headers: Headers::from(&vec![("Content-Type", "text/plain")]),
-            body: body.bytes().collect(),
+            body: body.into(),
+            trailers: Headers::from(&vec![]),
+            error: None,
+            from_handler: false,
         }
     }
 
-    /// Respuesta con el contenido de un archivo
-    pub fn file(path: &str) -> Self {
-        let path_buf = PathBuf::from(path);
-        let mut f = match File::open(path) {
+    /// Respuesta con el contenido de un archivo, que se envía por bloques sin cargarlo en memoria.
+    /// Incluye los validadores `ETag` y `Last-Modified`
+    pub fn file<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref();
+        let f = match File::open(path) {
             Ok(s) => s,
             Err(_) => return Self::not_found(),
         };
-        let mut body = Vec::<u8>::new();
-        if f.read_to_end(&mut body).is_err() {
-            return Self::internal_err("Could not read file");
-        }
-        Self {
+        let meta = match f.metadata() {
+            Ok(meta) => meta,
+            Err(_) => return Self::internal_err("Could not read file"),
+        };
+        let body = match Body::file(f) {
+            Ok(body) => body,
+            Err(_) => return Self::internal_err("Could not read file"),
+        };
+        let mut res = Self {
             status: StatusCode::OK,
-            headers: Headers::from(&vec![("Content-Type", mime_type(&path_buf))]),
+            headers: Headers::from(&vec![("Content-Type", mime_type(path))]),
             body,
+            trailers: Headers::from(&vec![]),
+            error: None,
+            from_handler: false,
+        };
+        let (etag, last_modified) = file_validators(&meta);
+        res.headers.insert("ETag".to_string(), etag);
+        if let Some(last_modified) = last_modified {
+            res.headers
+                .insert("Last-Modified".to_string(), last_modified);
+        }
+        res
+    }
+
+    /// Envía el cuerpo con `Transfer-Encoding: chunked`, sin `Content-Length`
+    pub fn set_chunked(&mut self) {
+        self.headers.remove("Content-Length");
+        self.headers
+            .insert("Transfer-Encoding".to_string(), "chunked".to_string());
+    }
+
+    /// Indica si el cuerpo se enviará en bloques (chunked)
+    pub fn is_chunked(&self) -> bool {
+        self.headers
+            .get("Transfer-Encoding")
+            .is_some_and(|te| te.to_ascii_lowercase().contains("chunked"))
+    }
+
+    /// Agrega un trailer; implica enviar el cuerpo chunked
+    pub fn set_trailer(&mut self, key: &str, value: &str) {
+        self.set_chunked();
+        self.trailers.insert(key.to_string(), value.to_string());
+    }
+
+    /// Respuesta con el contenido de un archivo, usando su versión precomprimida
+    /// (`<archivo>.br` o `<archivo>.gz`) si existe y el cliente la acepta.
+    /// El `Content-Type` es siempre el del archivo original. Atiende las requests
+    /// condicionales (304) y el header `Range`
+    pub fn file_for<P: AsRef<Path>>(req: &Request, path: P) -> Self {
+        Self::file_with(req, path.as_ref(), None)
+    }
+
+    /// Como `file_for`, pero tomando el contenido de `cache` cuando está vigente
+    pub fn cached_file_for<P: AsRef<Path>>(req: &Request, path: P, cache: &FileCache) -> Self {
+        Self::file_with(req, path.as_ref(), Some(cache))
+    }
+
+    /// Respuesta con el contenido de un archivo leído a través de `cache`.
+    /// Los archivos que no entran en la caché se envían desde el disco como `Response::file`
+    pub fn cached_file<P: AsRef<Path>>(path: P, cache: &FileCache) -> Self {
+        let path = path.as_ref();
+        let (data, meta) = match cache.get(path) {
+            Ok(Some(found)) => found,
+            Ok(None) => return Self::file(path),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Self::not_found(),
+            Err(_) => return Self::internal_err("Could not read file"),
+        };
+        let len = data.as_ref().len() as u64;
+        let mut res = Self::builder()
+            .header("Content-Type", mime_type(path))
+            .body(Body::reader(Cursor::new(data), Some(len)));
+        let (etag, last_modified) = file_validators(&meta);
+        res.headers.insert("ETag".to_string(), etag);
+        if let Some(last_modified) = last_modified {
+            res.headers
+                .insert("Last-Modified".to_string(), last_modified);
+        }
+        res
+    }
+
+    fn file_with(req: &Request, path: &Path, cache: Option<&FileCache>) -> Self {
+        let load = |path: &Path| match cache {
+            Some(cache) => Self::cached_file(path, cache),
+            None => Self::file(path),
+        };
+        if !path.is_file() {
+            return Self::not_found();
+        }
+
+        // Solo se consideran archivos regulares, no enlaces que podrían salir del directorio
+        let available: Vec<&str> = PRECOMPRESSED
+            .iter()
+            .filter(|(_, ext)| {
+                fs::symlink_metadata(sibling(path, ext)).is_ok_and(|m| m.file_type().is_file())
+            })
+            .map(|(enc, _)| *enc)
+            .collect();
+
+        let encoding = req
+            .headers
+            .get("Accept-Encoding")
+            .and_then(|accept| negotiate_encoding(accept, &available));
+        let (served, mut res) = match encoding {
+            Some(enc) => {
+                let ext = PRECOMPRESSED.iter().find(|(e, _)| *e == enc).unwrap().1;
+                let served = sibling(path, ext);
+                let mut res = load(&served);
+                res.headers
+                    .insert("Content-Type".to_string(), mime_type(path).to_string());
+                res.headers
+                    .insert("Content-Encoding".to_string(), enc.to_string());
+                (served, res)
+            }
+            None => (path.to_path_buf(), load(path)),
+        };
+        // La response depende de Accept-Encoding aunque esta vez se envíe el original
+        if !available.is_empty() {
+            res.headers
+                .insert("Vary".to_string(), "Accept-Encoding".to_string());
+        }
+
+        if is_not_modified(req, &res) {
+            return not_modified(&res);
         }
+        // Los rangos se refieren a los bytes del archivo enviado, comprimido o no
+        apply_range(req, &served, res)
     }
 
     pub fn set_cookie(&mut self, cookies: HashMap<String, String>) {
@@ -82,32 +363,106 @@ impl Response {
     }
 }
 
+impl Response {
+    // Arma la línea de estado y los headers, completando `Content-Length`, `Date`
+    // y `Connection` si el manejador no los definió. Indica además si la response
+    // lleva cuerpo y si va en chunks
+    fn head(&self) -> (String, bool, bool) {
+        let mut head = format!("HTTP/1.1 {}\r\n", self.status);
+        for (key, value) in self.headers.iter() {
+            head.push_str(&format!("{}: {}\r\n", key, value));
+        }
+
+        // 1xx, 204 y 304 no llevan cuerpo ni Content-Length (RFC 9110 8.6)
+        let has_body = !(self.status.is_informational()
+            || self.status == StatusCode::NOCONTENT
+            || self.status == StatusCode::NOTMODIFIED);
+        let chunked = has_body && self.is_chunked();
+        // Sin largo conocido ni chunked, el fin del cuerpo lo marca el cierre de la conexión
+        if let (true, false, None, Some(len)) = (
+            has_body,
+            chunked,
+            self.headers.get("Content-Length"),
+            self.body.len(),
+        ) {
+            head.push_str(&format!("Content-Length: {}\r\n", len));
+        }
+        // Anuncia los trailers que se enviarán al final
+        if chunked && !self.trailers.is_empty() && self.headers.get("Trailer").is_none() {
+            let mut names: Vec<&str> = self.trailers.iter().map(|(k, _)| k.as_str()).collect();
+            names.sort();
+            head.push_str(&format!("Trailer: {}\r\n", names.join(", ")));
+        }
+        if self.headers.get("Date").is_none() {
+            head.push_str(&format!("Date: {}\r\n", fmt_http_date(SystemTime::now())));
+        }
+        // El servidor atiende una única request por conexión
+        if self.headers.get("Connection").is_none() {
+            head.push_str("Connection: close\r\n");
+        }
+        head.push_str("\r\n");
+        (head, has_body, chunked)
+    }
+
+    /// Envía la response por un socket. El cuerpo de un archivo sin transformar
+    /// (sin compresión ni chunks) se copia con `sendfile(2)` en Linux
+    pub fn send_to(&self, stream: &mut TcpStream) -> Result<(), &'static str> {
+        let (head, has_body, chunked) = self.head();
+        match &self.body {
+            Body::File(file, len) if has_body && !chunked => {
+                stream
+                    .write_all(head.as_bytes())
+                    .or(Err("Failed sending headers data"))?;
+                send_file(&mut file.borrow_mut(), stream, *len).or(Err("Failed sending payload"))
+            }
+            _ => self.write_to(stream),
+        }
+    }
+}
+
 impl WriteTo for Response {
     type Error = &'static str;
 
-    /// Escribe la respuesta HTTP en el stream proporcionado
+    /// Escribe la respuesta HTTP en el stream proporcionado.
+    /// Completa `Content-Length`, `Date` y `Connection` si el manejador no los definió
     fn write_to<W: Write>(&self, stream: &mut W) -> Result<(), Self::Error> {
-        // Escribe la línea de estado
+        let (head, has_body, chunked) = self.head();
         stream
-            .write_fmt(format_args!("HTTP/1.1 {}\n", self.status as u32))
-            .or(Err("Failed sending status code"))?;
+            .write_all(head.as_bytes())
+            .or(Err("Failed sending headers data"))?;
 
-        // Escribe los headers
-        for (key, value) in self.headers.iter() {
-            stream
-                .write_fmt(format_args!("{}: {}\n", key, value))
-                .or(Err("Failed sending headers data"))?;
+        if !has_body {
+            return Ok(());
+        }
+        if !chunked {
+            // Escribe el body por bloques
+            return self
+                .body
+                .write_chunks(&mut |chunk| stream.write_all(chunk))
+                .or(Err("Failed sending payload"));
         }
 
-        // Escribe el separador entre headers y body
-        stream
-            .write_fmt(format_args!("\n"))
-            .or(Err("Failed sending body separator"))?;
+        // Cada bloque va precedido de su largo en hexadecimal; un bloque vacío cerraría el cuerpo
+        self.body
+            .write_chunks(&mut |chunk| {
+                if chunk.is_empty() {
+                    return Ok(());
+                }
+                stream.write_all(format!("{:X}\r\n", chunk.len()).as_bytes())?;
+                stream.write_all(chunk)?;
+                stream.write_all(b"\r\n")
+            })
+            .or(Err("Failed sending payload"))?;
 
-        // Escribe el body
+        // Bloque final y trailers
+        let mut tail = String::from("0\r\n");
+        for (key, value) in self.trailers.iter() {
+            tail.push_str(&format!("{}: {}\r\n", key, value));
+        }
+        tail.push_str("\r\n");
         stream
-            .write_all(&self.body)
-            .or(Err("Failed sending payload"))?;
+            .write_all(tail.as_bytes())
+            .or(Err("Failed sending trailers"))?;
 
         Ok(())
     }
@@ -116,6 +471,7 @@ impl WriteTo for Response {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::http::Method;
     use std::io::Cursor;
 
     #[test]
@@ -142,6 +498,19 @@ mod tests {
         assert_eq!(response.body, b"Hello, world!");
     }
 
+    #[test]
+    // prueba de respuesta con una plantilla renderizada
+    fn test_render_response() {
+        let template = Template::parse("<p>Hola {{ name }}</p>").unwrap();
+        let response = Response::render(&template, &Context::new().with("name", "<ana>"));
+        assert_eq!(response.status, StatusCode::OK);
+        assert_eq!(
+            response.headers.get("Content-Type"),
+            Some(&"text/html; charset=utf-8".to_string())
+        );
+        assert_eq!(response.body, b"<p>Hola &lt;ana&gt;</p>");
+    }
+
     #[test]
     // prueba de respuesta 404 Not Found
     fn test_not_found_response() {
@@ -177,6 +546,174 @@ mod tests {
             .contains("user=john"));
     }
 
+    #[test]
+    // prueba de archivos precomprimidos según Accept-Encoding
+    fn test_file_precompressed() {
+        let dir = std::env::temp_dir().join(format!("precompressed-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let js = dir.join("app.js");
+        fs::write(&js, "let x = 1;").unwrap();
+        fs::write(dir.join("app.js.gz"), "gz").unwrap();
+        fs::write(dir.join("app.js.br"), "br").unwrap();
+
+        let request = |accept: &str| Request {
+            headers: Headers::from(&vec![("Accept-Encoding", accept)]),
+            ..Request::new(Method::GET, "/app.js")
+        };
+
+        let res = Response::file_for(&request("gzip, br"), &js);
+        assert_eq!(res.headers.get("Content-Encoding"), Some(&"br".to_string()));
+        assert_eq!(
+            res.headers.get("Content-Type"),
+            Some(&"text/javascript".to_string())
+        );
+        assert_eq!(
+            res.headers.get("Vary"),
+            Some(&"Accept-Encoding".to_string())
+        );
+        assert_eq!(res.body.into_bytes().unwrap(), b"br");
+
+        let res = Response::file_for(&request("gzip"), &js);
+        assert_eq!(
+            res.headers.get("Content-Encoding"),
+            Some(&"gzip".to_string())
+        );
+        assert_eq!(res.body.into_bytes().unwrap(), b"gz");
+
+        let res = Response::file_for(&request("identity"), &js);
+        assert!(res.headers.get("Content-Encoding").is_none());
+        assert_eq!(
+            res.headers.get("Vary"),
+            Some(&"Accept-Encoding".to_string())
+        );
+        assert_eq!(res.body.into_bytes().unwrap(), b"let x = 1;");
+
+        // sin el original no se sirve la versión comprimida
+        fs::remove_file(&js).unwrap();
+        let res = Response::file_for(&request("gzip"), &js);
+        assert_eq!(res.status, StatusCode::NOTFOUND);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    // prueba de validadores y respuestas 304 para archivos
+    fn test_file_conditional() {
+        let res = Response::file("Cargo.toml");
+        let etag = res.headers.get("ETag").unwrap().clone();
+        let last_modified = res.headers.get("Last-Modified").unwrap().clone();
+        assert!(etag.starts_with('"') && etag.ends_with('"'));
+
+        let request = |key: &str, value: &str| Request {
+            headers: Headers::from(&vec![(key, value)]),
+            ..Request::new(Method::GET, "/Cargo.toml")
+        };
+
+        let res = Response::file_for(&request("If-None-Match", &etag), "Cargo.toml");
+        assert_eq!(res.status, StatusCode::NOTMODIFIED);
+        assert!(res.body.is_empty());
+        assert_eq!(res.headers.get("ETag"), Some(&etag));
+
+        let res = Response::file_for(&request("If-Modified-Since", &last_modified), "Cargo.toml");
+        assert_eq!(res.status, StatusCode::NOTMODIFIED);
+
+        let res = Response::file_for(&request("If-None-Match", "\"otro\""), "Cargo.toml");
+        assert_eq!(res.status, StatusCode::OK);
+        assert!(!res.body.is_empty());
+    }
+
+    #[test]
+    // prueba de archivos servidos desde la caché en memoria
+    fn test_cached_file() {
+        let cache = FileCache::new(1 << 20);
+        let expected = fs::read("Cargo.toml").unwrap();
+
+        let res = Response::cached_file("Cargo.toml", &cache);
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(
+            res.headers.get("ETag"),
+            Response::file("Cargo.toml").headers.get("ETag")
+        );
+        assert_eq!(res.body.len(), Some(expected.len() as u64));
+        assert_eq!(res.body.into_bytes().unwrap(), expected);
+
+        let res = Response::cached_file("Cargo.toml", &cache);
+        assert_eq!(res.body.into_bytes().unwrap(), expected);
+        assert_eq!((cache.stats().hits, cache.stats().misses), (1, 1));
+
+        let res = Response::cached_file("no-existe.txt", &cache);
+        assert_eq!(res.status, StatusCode::NOTFOUND);
+
+        // un archivo que no entra en la caché se envía desde el disco sin leerlo entero
+        let small = FileCache::new(16);
+        let res = Response::cached_file("Cargo.toml", &small);
+        assert!(matches!(res.body, Body::File(..)));
+        assert_eq!(res.body.len(), Some(expected.len() as u64));
+        assert_eq!(small.stats().entries, 0);
+    }
+
+    #[test]
+    // prueba del constructor incremental
+    fn test_builder() {
+        let response = Response::builder()
+            .status(StatusCode::ACCEPTED)
+            .header("Content-Type", "application/json")
+            .header("X-Request-Id", "42")
+            .body("{}");
+        assert_eq!(response.status, StatusCode::ACCEPTED);
+        assert_eq!(
+            response.headers.get("Content-Type"),
+            Some(&"application/json".to_string())
+        );
+        assert_eq!(
+            response.headers.get("X-Request-Id"),
+            Some(&"42".to_string())
+        );
+        assert_eq!(response.body, b"{}");
+        assert!(Response::builder().body(vec![]).body.is_empty());
+    }
+
+    #[test]
+    // prueba de los constructores para códigos comunes
+    fn test_common_statuses() {
+        let response = Response::created("/users/7", "created");
+        assert_eq!(response.status, StatusCode::CREATED);
+        assert_eq!(
+            response.headers.get("Location"),
+            Some(&"/users/7".to_string())
+        );
+
+        let response = Response::no_content();
+        assert_eq!(response.status, StatusCode::NOCONTENT);
+        assert!(response.body.is_empty());
+
+        let response = Response::unauthorized("admin");
+        assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers.get("WWW-Authenticate"),
+            Some(&"Basic realm=\"admin\"".to_string())
+        );
+
+        let response = Response::too_many_requests(30);
+        assert_eq!(response.status, StatusCode::TOOMANYREQUESTS);
+        assert_eq!(response.headers.get("Retry-After"), Some(&"30".to_string()));
+
+        let response = Response::unavailable(120);
+        assert_eq!(response.status, StatusCode::UNAVAILABLE);
+        assert_eq!(
+            response.headers.get("Retry-After"),
+            Some(&"120".to_string())
+        );
+
+        assert_eq!(Response::bad_request("x").status, StatusCode::BADREQUEST);
+        assert_eq!(Response::forbidden("x").status, StatusCode::FORBIDDEN);
+        assert_eq!(Response::conflict("x").status, StatusCode::CONFLICT);
+        assert_eq!(
+            Response::unprocessable("x").status,
+            StatusCode::UNPROCESSABLE
+        );
+    }
+
     #[test]
     // prueba de escritura en un stream
     fn test_write_to() {
@@ -185,8 +722,86 @@ mod tests {
         response.write_to(&mut buffer).unwrap();
 
         let written = String::from_utf8(buffer.into_inner()).unwrap();
-        assert!(written.starts_with("HTTP/1.1 200\n"));
-        assert!(written.contains("Content-Type: text/plain\n"));
-        assert!(written.ends_with("\n\nTest body"));
+        assert!(written.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(written.contains("Content-Type: text/plain\r\n"));
+        assert!(written.contains("Content-Length: 9\r\n"));
+        assert!(written.contains("Date: "));
+        assert!(written.contains("Connection: close\r\n"));
+        assert!(written.ends_with("\r\n\r\nTest body"));
+        assert!(!written.replace("\r\n", "").contains('\n'));
+    }
+
+    #[test]
+    // prueba de envío en streaming de archivos y cuerpos generados
+    fn test_write_to_streaming() {
+        let response = Response::file("Cargo.toml");
+        assert!(response.body.as_bytes().is_none());
+        let expected = std::fs::read("Cargo.toml").unwrap();
+        let mut buffer = Vec::new();
+        response.write_to(&mut buffer).unwrap();
+        let written = String::from_utf8(buffer).unwrap();
+        assert!(written.contains(&format!("Content-Length: {}\r\n", expected.len())));
+        assert!(written.ends_with(std::str::from_utf8(&expected).unwrap()));
+
+        let response = Response::builder().body(Body::iter(
+            vec![b"uno ".to_vec(), b"dos".to_vec()].into_iter(),
+        ));
+        let mut buffer = Vec::new();
+        response.write_to(&mut buffer).unwrap();
+        let written = String::from_utf8(buffer).unwrap();
+        assert!(!written.contains("Content-Length"));
+        assert!(written.ends_with("\r\n\r\nuno dos"));
+    }
+
+    #[test]
+    // prueba de la codificación chunked con trailers
+    fn test_write_to_chunked() {
+        let mut response = Response::builder().body(Body::iter(
+            vec![b"hola ".to_vec(), vec![], b"mundo!".to_vec()].into_iter(),
+        ));
+        response.set_trailer("X-Checksum", "abc");
+        assert!(response.is_chunked());
+
+        let mut buffer = Vec::new();
+        response.write_to(&mut buffer).unwrap();
+        let written = String::from_utf8(buffer).unwrap();
+        assert!(written.contains("Transfer-Encoding: chunked\r\n"));
+        assert!(written.contains("Trailer: X-Checksum\r\n"));
+        assert!(!written.contains("Content-Length"));
+        assert!(
+            written.ends_with("\r\n\r\n5\r\nhola \r\n6\r\nmundo!\r\n0\r\nX-Checksum: abc\r\n\r\n")
+        );
+
+        // set_chunked descarta un Content-Length explícito
+        let mut response = Response::builder()
+            .header("content-length", "3")
+            .body("abc");
+        response.set_chunked();
+        let mut buffer = Vec::new();
+        response.write_to(&mut buffer).unwrap();
+        let written = String::from_utf8(buffer).unwrap();
+        assert!(!written.to_lowercase().contains("content-length"));
+        assert!(written.ends_with("\r\n\r\n3\r\nabc\r\n0\r\n\r\n"));
+    }
+
+    #[test]
+    // prueba de que los headers explícitos no se duplican y 204 no lleva Content-Length
+    fn test_write_to_explicit_headers() {
+        let response = Response::builder()
+            .header("Connection", "keep-alive")
+            .header("Content-Length", "4")
+            .body("abcd");
+        let mut buffer = Vec::new();
+        response.write_to(&mut buffer).unwrap();
+        let written = String::from_utf8(buffer).unwrap();
+        assert_eq!(written.matches("Content-Length").count(), 1);
+        assert!(written.contains("Connection: keep-alive\r\n"));
+        assert!(!written.contains("Connection: close"));
+
+        let mut buffer = Vec::new();
+        Response::no_content().write_to(&mut buffer).unwrap();
+        let written = String::from_utf8(buffer).unwrap();
+        assert!(written.starts_with("HTTP/1.1 204 No Content\r\n"));
+        assert!(!written.contains("Content-Length"));
     }
 }
@@ -1,13 +1,34 @@
-use super::{Headers, Request, Response, StatusCode};
+use super::{
+    files, mime_type, CachePolicy, FileCache, IntoResponse, Method, Middleware, Next, Path,
+    Request, Response,
+};
 use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
 
-/// Tipo que representa una función de callback para manejar requests
-pub type Callback = fn(&Request) -> Response;
+/// Tipo que representa una función de callback para manejar requests.
+/// Acepta tanto punteros a función como closures que capturan estado.
+pub type Callback = Arc<dyn Fn(&Request) -> Response + Send + Sync + 'static>;
 
 /// Estructura que maneja el enrutamiento de requests
 #[derive(Clone)]
 pub struct Router {
-    handlers: HashMap<String, Handler>,
+    handlers: HashMap<String, Route>,
+    mounts: Vec<(String, Router)>,
+    trailing_slash: TrailingSlash,
+    cache_control: CachePolicy,
+    file_cache: Option<Arc<FileCache>>,
+}
+
+/// Política para rutas que solo difieren en la '/' final (`/login` y `/login/`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrailingSlash {
+    /// Solo coincide la ruta exacta
+    Strict,
+    /// Redirige (301) a la variante registrada
+    Redirect,
+    /// Atiende la request con la variante registrada
+    Ignore,
 }
 
 /// Enum que representa los tipos de manejadores de rutas
@@ -15,6 +36,74 @@ pub struct Router {
 enum Handler {
     Callback(Callback),
     File(String),
+    Dir { root: String, listing: bool },
+}
+
+/// Ruta registrada: su manejador y los middlewares que solo aplican a ella
+#[derive(Clone)]
+struct Route {
+    handler: Handler,
+    middlewares: Vec<Arc<dyn Middleware>>,
+    name: Option<String>,
+}
+
+/// Tipo de manejador de una ruta, tal como lo expone `Router::routes`
+#[derive(Debug, Clone, PartialEq)]
+pub enum RouteKind {
+    Callback,
+    File(String),
+    Dir(String),
+}
+
+/// Descripción de una ruta registrada
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteInfo {
+    pub pattern: String,
+    /// Métodos aceptados; vacío significa cualquiera, como en todas las rutas del Router
+    pub methods: Vec<Method>,
+    pub kind: RouteKind,
+    pub name: Option<String>,
+}
+
+/// Destino de una ruta que coincide por prefijo
+enum Target<'a> {
+    Dir(&'a Route),
+    Router(&'a Router),
+}
+
+impl Handler {
+    /// Ejecuta el manejador sobre la request; los archivos se leen de `cache` si hay una
+    fn call(&self, req: &Request, cache: Option<&FileCache>) -> Response {
+        match self {
+            Handler::Callback(cb) => cb(req),
+            Handler::File(fname) => match cache {
+                Some(cache) => Response::cached_file_for(req, fname, cache),
+                None => Response::file_for(req, fname),
+            },
+            Handler::Dir { root, listing } => files::serve_dir(req, root, *listing),
+        }
+    }
+}
+
+impl Route {
+    fn new(handler: Handler) -> Self {
+        Self {
+            handler,
+            middlewares: Vec::new(),
+            name: None,
+        }
+    }
+
+    /// Ejecuta el manejador envuelto en los middlewares de la ruta
+    fn call(&self, req: &Request, cache: Option<&FileCache>) -> Response {
+        if self.middlewares.is_empty() {
+            return self.handler.call(req, cache);
+        }
+        // Los middlewares pueden modificar la request, así que trabajan sobre una copia
+        let mut req = req.clone();
+        let endpoint = |req: &mut Request| self.handler.call(req, cache);
+        Next::new(&self.middlewares, &endpoint).run(&mut req)
+    }
 }
 
 impl Router {
@@ -22,33 +111,239 @@ impl Router {
     pub fn new() -> Self {
         Self {
             handlers: HashMap::new(),
+            mounts: Vec::new(),
+            trailing_slash: TrailingSlash::Strict,
+            cache_control: CachePolicy::new(),
+            file_cache: None,
         }
     }
 
     /// Maneja una request y devuelve la Response apropiada
     pub fn handle_request(&self, req: &Request) -> Response {
-        // Busca un manejador que coincida con la ruta de la request
-        let handler = match self.handlers.iter().find(|(k, _)| req.path == **k) {
-            Some((_, v)) => v,
+        // Busca un manejador que coincida exactamente con la ruta de la request
+        if let Some(route) = self.find_exact(&req.path) {
+            return self.call_route(route, req);
+        }
+
+        // Prueba la variante con o sin '/' final según la política configurada
+        if self.trailing_slash != TrailingSlash::Strict {
+            if let Some(alt) = req.path.toggle_trailing_slash() {
+                if let Some(route) = self.find_exact(&alt) {
+                    if self.trailing_slash == TrailingSlash::Redirect {
+                        return Response::redirect(&alt.to_string());
+                    }
+                    let mut inner = req.clone();
+                    inner.path = alt;
+                    return self.call_route(route, &inner);
+                }
+            }
+        }
+
+        self.handle_prefixed(req)
+    }
+
+    /// Ejecuta una ruta y aplica la política de caché a las responses de archivos
+    fn call_route(&self, route: &Route, req: &Request) -> Response {
+        let mut res = route.call(req, self.file_cache.as_deref());
+        if self.cache_control.is_empty() {
+            return res;
+        }
+        // El tipo MIME sale del archivo servido, que puede no coincidir con la URL (/login)
+        let file = match &route.handler {
+            Handler::Callback(_) => return res,
+            Handler::File(fname) => fname.as_str(),
+            Handler::Dir { .. } if req.path.as_str().ends_with('/') => "index.html",
+            Handler::Dir { .. } => req.path.as_str(),
+        };
+        let full_path = format!("{}{}", req.path.base(), req.path.as_str());
+        let mime = mime_type(std::path::Path::new(file));
+        self.cache_control.apply(&full_path, mime, &mut res);
+        res
+    }
+
+    /// Busca una ruta (que no sea un directorio) registrada exactamente con esta ruta
+    fn find_exact(&self, path: &Path) -> Option<&Route> {
+        self.handlers
+            .iter()
+            .find(|(k, r)| !matches!(r.handler, Handler::Dir { .. }) && *path == **k)
+            .map(|(_, r)| r)
+    }
+
+    /// Configura la política para la '/' final de las rutas de este Router
+    pub fn set_trailing_slash(&mut self, policy: TrailingSlash) {
+        self.trailing_slash = policy;
+    }
+
+    /// Delega la request al directorio o sub-router con el prefijo más largo que coincida
+    fn handle_prefixed(&self, req: &Request) -> Response {
+        let dirs = self
+            .handlers
+            .iter()
+            .filter_map(|(prefix, route)| match route.handler {
+                Handler::Dir { .. } => Some((prefix, Target::Dir(route))),
+                _ => None,
+            });
+        let mounts = self
+            .mounts
+            .iter()
+            .map(|(prefix, router)| (prefix, Target::Router(router)));
+
+        let matched = dirs
+            .chain(mounts)
+            .filter_map(|(prefix, target)| {
+                Some((prefix.len(), target, req.path.strip_prefix(prefix)?))
+            })
+            .max_by_key(|(len, _, _)| *len);
+
+        let (_, target, path) = match matched {
+            Some(m) => m,
             None => return Response::not_found(),
         };
 
-        // Ejecuta el manejador correspondiente
-        match handler {
-            Handler::Callback(cb) => cb(req),
-            Handler::File(fname) => Response::file(fname),
+        // El destino ve la ruta sin el prefijo; el prefijo queda en `path.base()`
+        let mut inner = req.clone();
+        inner.path = path;
+        match target {
+            Target::Dir(route) => self.call_route(route, &inner),
+            Target::Router(router) => router.handle_request(&inner),
+        }
+    }
+
+    /// Monta un Router completo bajo un prefijo de ruta
+    pub fn mount(&mut self, prefix: &str, mut router: Router) {
+        // El sub-router usa la caché de archivos de este Router si no tiene una propia
+        if let (Some(cache), None) = (&self.file_cache, &router.file_cache) {
+            router.set_file_cache(cache.clone());
         }
+        self.mounts
+            .push((prefix.trim_end_matches('/').to_string(), router));
     }
 
-    /// Inserta un nuevo manejador de tipo Callback
-    pub fn insert_callback(&mut self, pat: &str, cb: Callback) {
-        self.handlers.insert(pat.to_string(), Handler::Callback(cb));
+    /// Inserta un nuevo manejador de tipo Callback.
+    /// El callback puede devolver `Response` o `Result<Response, HttpError>`
+    pub fn insert_callback<F, R>(&mut self, pat: &str, cb: F)
+    where
+        F: Fn(&Request) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        // Las responses armadas por el callback (no los HttpError) quedan marcadas
+        // para que los manejadores de error del servidor no las reemplacen
+        let cb = move |req: &Request| {
+            let mut res = cb(req).into_response();
+            res.from_handler = res.error.is_none();
+            res
+        };
+        self.handlers
+            .insert(pat.to_string(), Route::new(Handler::Callback(Arc::new(cb))));
     }
 
     /// Inserta un nuevo manejador de tipo File
     pub fn insert_file(&mut self, pat: &str, fname: &str) {
+        self.handlers.insert(
+            pat.to_string(),
+            Route::new(Handler::File(fname.to_string())),
+        );
+    }
+
+    /// Inserta un directorio cuyos archivos se sirven bajo el prefijo dado.
+    /// Con `listing`, los directorios sin `index.html` muestran su contenido
+    pub fn insert_dir(&mut self, pat: &str, root: &str, listing: bool) {
+        self.handlers.insert(
+            pat.trim_end_matches('/').to_string(),
+            Route::new(Handler::Dir {
+                root: root.to_string(),
+                listing,
+            }),
+        );
+    }
+
+    /// Asigna `Cache-Control` a los archivos de este Router cuya ruta coincide con `pattern`
+    pub fn set_cache_control(&mut self, pattern: &str, value: &str) {
+        self.cache_control.path(pattern, value);
+    }
+
+    /// Asigna `Cache-Control` a los archivos de este Router del tipo MIME `mime` (admite '*')
+    pub fn set_cache_control_mime(&mut self, mime: &str, value: &str) {
+        self.cache_control.mime(mime, value);
+    }
+
+    /// Sirve los archivos de las rutas `File` desde una caché en memoria.
+    /// Se aplica también a los sub-routers montados
+    pub fn set_file_cache(&mut self, cache: Arc<FileCache>) {
+        for (_, router) in &mut self.mounts {
+            router.set_file_cache(cache.clone());
+        }
+        self.file_cache = Some(cache);
+    }
+
+    /// Busca una ruta ya registrada; los errores de configuración terminan en pánico
+    fn route_mut(&mut self, pat: &str) -> &mut Route {
+        let key = if self.handlers.contains_key(pat) {
+            pat
+        } else {
+            pat.trim_end_matches('/')
+        };
         self.handlers
-            .insert(pat.to_string(), Handler::File(fname.to_string()));
+            .get_mut(key)
+            .unwrap_or_else(|| panic!("No route registered for '{}'", pat))
+    }
+
+    /// Agrega un middleware que solo se ejecuta para la ruta dada.
+    /// La ruta debe estar registrada previamente
+    pub fn insert_route_middleware<M: Middleware + 'static>(&mut self, pat: &str, middleware: M) {
+        self.route_mut(pat).middlewares.push(Arc::new(middleware));
+    }
+
+    /// Asigna un nombre descriptivo a una ruta
+    pub fn set_name(&mut self, pat: &str, name: &str) {
+        self.route_mut(pat).name = Some(name.to_string());
+    }
+
+    /// Devuelve todas las rutas registradas, incluidas las de los sub-routers
+    /// montados (con su prefijo), ordenadas por patrón
+    pub fn routes(&self) -> Vec<RouteInfo> {
+        let mut routes: Vec<RouteInfo> = self
+            .handlers
+            .iter()
+            .map(|(pattern, route)| RouteInfo {
+                pattern: pattern.clone(),
+                methods: Vec::new(),
+                kind: match &route.handler {
+                    Handler::Callback(_) => RouteKind::Callback,
+                    Handler::File(fname) => RouteKind::File(fname.clone()),
+                    Handler::Dir { root, .. } => RouteKind::Dir(root.clone()),
+                },
+                name: route.name.clone(),
+            })
+            .collect();
+
+        for (prefix, router) in &self.mounts {
+            routes.extend(router.routes().into_iter().map(|mut info| {
+                info.pattern = format!("{}{}", prefix, info.pattern);
+                info
+            }));
+        }
+
+        routes.sort_by(|a, b| a.pattern.cmp(&b.pattern));
+        routes
+    }
+
+    /// Devuelve los archivos y directorios registrados que no existen en disco
+    pub fn missing_files(&self) -> Vec<String> {
+        self.routes()
+            .into_iter()
+            .filter_map(|info| match info.kind {
+                RouteKind::File(f) if !std::path::Path::new(&f).is_file() => Some(f),
+                RouteKind::Dir(d) if !std::path::Path::new(&d).is_dir() => Some(d),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // Verifica si hay un Router montado en el prefijo dado
+    pub fn has_mount(&self, prefix: &str) -> bool {
+        let prefix = prefix.trim_end_matches('/');
+        self.mounts.iter().any(|(p, _)| p == prefix)
     }
 
     // Verifica si una ruta tiene un manejador asociado
@@ -64,10 +359,41 @@ impl Router {
     }
 }
 
+impl Default for Router {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Print de una ruta como fila de la tabla de rutas
+impl fmt::Display for RouteInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let methods = if self.methods.is_empty() {
+            "*".to_string()
+        } else {
+            let names: Vec<String> = self.methods.iter().map(|m| format!("{:?}", m)).collect();
+            names.join(",")
+        };
+        let kind = match &self.kind {
+            RouteKind::Callback => "callback".to_string(),
+            RouteKind::File(f) => format!("file {}", f),
+            RouteKind::Dir(d) => format!("dir {}", d),
+        };
+        write!(f, "{:<12} {:<24} {}", methods, self.pattern, kind)?;
+        if let Some(name) = &self.name {
+            write!(f, " ({})", name)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
-    use super::{Headers, Request, Response, Router, StatusCode};
+    use super::{Middleware, Request, Response, RouteInfo, RouteKind, Router, TrailingSlash};
+    use crate::http::{Headers, Method, StatusCode};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
 
     #[test]
     // Prueba de router con archivo
@@ -87,15 +413,16 @@ mod tests {
         let headers = Headers::from(&vec);
 
         let request: Request = Request {
-            method: "GET".into(),
-            path: "/test".into(),
-            headers: headers,
-            body: String::new(),
+            headers,
+            ..Request::new(Method::GET, "/test")
         };
 
         let response = router.handle_request(&request);
 
-        assert_eq!(String::from_utf8_lossy(&response.body), "Callback response");
+        assert_eq!(
+            String::from_utf8_lossy(response.body.as_bytes().unwrap()),
+            "Callback response"
+        );
         assert_eq!(response.status, StatusCode::OK);
     }
 
@@ -111,14 +438,234 @@ mod tests {
         let headers = Headers::from(&vec);
 
         let request: Request = Request {
-            method: "GET".into(),
-            path: "/test".into(),
-            headers: headers,
-            body: String::new(),
+            headers,
+            ..Request::new(Method::GET, "/test")
         };
 
         let response = router.handle_request(&request);
 
         assert_eq!(response.status, StatusCode::NOTFOUND);
     }
+
+    #[test]
+    // Prueba de router con un closure que captura estado
+    fn test_router_with_closure_state() {
+        let mut router = Router::new();
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        let counter_cb = Arc::clone(&counter);
+        router.insert_callback("/count", move |_req: &Request| {
+            let n = counter_cb.fetch_add(1, Ordering::SeqCst) + 1;
+            Response::ok(&n.to_string())
+        });
+
+        for expected in ["1", "2"] {
+            let request: Request = Request::new(Method::GET, "/count");
+            let response = router.handle_request(&request);
+            assert_eq!(
+                String::from_utf8_lossy(response.body.as_bytes().unwrap()),
+                expected
+            );
+        }
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    // Prueba de sub-routers montados bajo un prefijo
+    fn test_router_mount() {
+        let mut api = Router::new();
+        api.insert_callback("/users", |req: &Request| {
+            Response::ok(&format!("{} {}", req.path.base(), req.path.as_str()))
+        });
+        let mut admin = Router::new();
+        admin.insert_callback("/", |_req: &Request| Response::ok("admin"));
+        api.mount("/admin", admin);
+
+        let mut router = Router::new();
+        router.mount("/api/", api);
+        assert!(router.has_mount("/api"));
+
+        let request = |path: &str| Request::new(Method::GET, path);
+
+        let response = router.handle_request(&request("/api/users"));
+        assert_eq!(
+            String::from_utf8_lossy(response.body.as_bytes().unwrap()),
+            "/api /users"
+        );
+
+        let response = router.handle_request(&request("/api/admin"));
+        assert_eq!(
+            String::from_utf8_lossy(response.body.as_bytes().unwrap()),
+            "admin"
+        );
+
+        let response = router.handle_request(&request("/apiusers"));
+        assert_eq!(response.status, StatusCode::NOTFOUND);
+    }
+
+    #[test]
+    // Prueba de directorio estático bajo un prefijo
+    fn test_router_dir() {
+        let root = std::env::temp_dir().join(format!("http-router-dir-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("app.js"), "let x = 1;").unwrap();
+
+        let mut router = Router::new();
+        router.insert_dir("/static/", root.to_str().unwrap(), false);
+        router.insert_callback("/static/api", |_req: &Request| Response::ok("callback"));
+
+        let request = |path: &str| Request::new(Method::GET, path);
+
+        let response = router.handle_request(&request("/static/app.js"));
+        assert_eq!(response.body.into_bytes().unwrap(), b"let x = 1;");
+        assert_eq!(
+            response.headers.get("Content-Type"),
+            Some(&"text/javascript".to_string())
+        );
+
+        // Las rutas exactas tienen prioridad sobre el directorio
+        let response = router.handle_request(&request("/static/api"));
+        assert_eq!(response.body, b"callback");
+
+        let response = router.handle_request(&request("/static/../Cargo.toml"));
+        assert_eq!(response.status, StatusCode::NOTFOUND);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    // Prueba de políticas de Cache-Control para archivos y directorios
+    fn test_router_cache_control() {
+        let root = std::env::temp_dir().join(format!("http-router-cache-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("app.3f2a.js"), "let x = 1;").unwrap();
+        std::fs::write(root.join("index.html"), "<h1>home</h1>").unwrap();
+
+        let mut router = Router::new();
+        router.insert_dir("/assets", root.to_str().unwrap(), false);
+        router.insert_file("/login", root.join("index.html").to_str().unwrap());
+        router.insert_callback("/api", |_req: &Request| Response::ok("api"));
+        router.set_cache_control("/assets/*.js", "public, max-age=31536000, immutable");
+        router.set_cache_control_mime("text/html", "no-cache");
+
+        let request = |path: &str| Request::new(Method::GET, path);
+        let cache_control = |path: &str| {
+            router
+                .handle_request(&request(path))
+                .headers
+                .get("Cache-Control")
+                .cloned()
+        };
+
+        assert_eq!(
+            cache_control("/assets/app.3f2a.js").as_deref(),
+            Some("public, max-age=31536000, immutable")
+        );
+        // el tipo MIME se toma del archivo servido, no de la URL
+        assert_eq!(cache_control("/login").as_deref(), Some("no-cache"));
+        assert_eq!(cache_control("/assets/").as_deref(), Some("no-cache"));
+        // los callbacks y los errores no reciben la política
+        assert_eq!(cache_control("/api"), None);
+        assert_eq!(cache_control("/assets/missing.js"), None);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    // Prueba de middlewares por ruta
+    fn test_route_middleware() {
+        struct Deny;
+        impl Middleware for Deny {
+            fn before(&self, _req: &mut Request) -> Option<Response> {
+                Some(Response::internal_err("denied"))
+            }
+        }
+
+        let mut router = Router::new();
+        router.insert_callback("/open", |_req: &Request| Response::ok("open"));
+        router.insert_callback("/closed", |_req: &Request| Response::ok("closed"));
+        router.insert_route_middleware("/closed", Deny);
+
+        let request = |path: &str| Request::new(Method::GET, path);
+
+        assert_eq!(router.handle_request(&request("/open")).body, b"open");
+        assert_eq!(router.handle_request(&request("/closed")).body, b"denied");
+    }
+
+    #[test]
+    // Prueba de introspección de rutas y archivos faltantes
+    fn test_routes() {
+        let mut api = Router::new();
+        api.insert_callback("/login", |_req: &Request| Response::ok("login"));
+        api.set_name("/login", "login");
+
+        let mut router = Router::new();
+        router.insert_file("/index.html", "./static/index.html");
+        router.insert_file("/gone", "./static/does-not-exist.html");
+        router.insert_dir("/assets/", "./static", false);
+        router.mount("/api", api);
+
+        let routes = router.routes();
+        assert_eq!(
+            routes,
+            vec![
+                RouteInfo {
+                    pattern: "/api/login".to_string(),
+                    methods: vec![],
+                    kind: RouteKind::Callback,
+                    name: Some("login".to_string()),
+                },
+                RouteInfo {
+                    pattern: "/assets".to_string(),
+                    methods: vec![],
+                    kind: RouteKind::Dir("./static".to_string()),
+                    name: None,
+                },
+                RouteInfo {
+                    pattern: "/gone".to_string(),
+                    methods: vec![],
+                    kind: RouteKind::File("./static/does-not-exist.html".to_string()),
+                    name: None,
+                },
+                RouteInfo {
+                    pattern: "/index.html".to_string(),
+                    methods: vec![],
+                    kind: RouteKind::File("./static/index.html".to_string()),
+                    name: None,
+                },
+            ]
+        );
+        assert!(routes[0].to_string().starts_with("*            /api/login"));
+        assert!(routes[0].to_string().ends_with("callback (login)"));
+        assert_eq!(
+            router.missing_files(),
+            vec!["./static/does-not-exist.html".to_string()]
+        );
+    }
+
+    #[test]
+    // Prueba de las políticas para la '/' final
+    fn test_trailing_slash() {
+        let request = |path: &str| Request::new(Method::GET, path);
+
+        let mut router = Router::new();
+        router.insert_callback("/login", |_req: &Request| Response::ok("login"));
+        router.insert_callback("/docs/", |_req: &Request| Response::ok("docs"));
+
+        let response = router.handle_request(&request("/login/"));
+        assert_eq!(response.status, StatusCode::NOTFOUND);
+
+        router.set_trailing_slash(TrailingSlash::Redirect);
+        let response = router.handle_request(&request("/login/?next=a"));
+        assert_eq!(response.status, StatusCode::REDIRECT);
+        assert_eq!(
+            response.headers.get("Location"),
+            Some(&"/login?next=a".to_string())
+        );
+
+        router.set_trailing_slash(TrailingSlash::Ignore);
+        assert_eq!(router.handle_request(&request("/login/")).body, b"login");
+        assert_eq!(router.handle_request(&request("/docs")).body, b"docs");
+        assert_eq!(router.handle_request(&request("//docs/./")).body, b"docs");
+    }
 }
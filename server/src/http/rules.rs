@@ -0,0 +1,227 @@
+use super::utils::{substitute, wildcard_match};
+use super::{Path, Request, Response, StatusCode};
+use std::fs;
+
+/// Acción de una regla cuando su patrón coincide
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleAction {
+    /// Responde con una redirección al destino
+    Redirect(StatusCode),
+    /// Cambia internamente la ruta de la request antes de enrutarla
+    Rewrite,
+}
+
+/// Regla de redirección o reescritura. En `pattern` cada '*' captura una parte
+/// de la ruta, que se puede usar en `target` como `$1`, `$2`, ...
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub pattern: String,
+    pub target: String,
+    pub action: RuleAction,
+}
+
+/// Tabla de reglas, evaluadas en orden de registro
+#[derive(Debug, Clone, Default)]
+pub struct Rules {
+    rules: Vec<Rule>,
+}
+
+impl Rules {
+    /// Crea una tabla vacía
+    pub fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// Agrega una redirección; `status` debe ser un código 3xx de redirección
+    /// (301, 302, 303, 307 o 308), si no la configuración termina en pánico
+    pub fn redirect(&mut self, pattern: &str, target: &str, status: StatusCode) {
+        if !is_redirect(status) {
+            panic!("Invalid redirect status '{}' for '{}'", status, pattern);
+        }
+        self.rules.push(Rule {
+            pattern: pattern.to_string(),
+            target: target.to_string(),
+            action: RuleAction::Redirect(status),
+        });
+    }
+
+    /// Agrega una reescritura interna de la ruta
+    pub fn rewrite(&mut self, pattern: &str, target: &str) {
+        self.rules.push(Rule {
+            pattern: pattern.to_string(),
+            target: target.to_string(),
+            action: RuleAction::Rewrite,
+        });
+    }
+
+    /// Cantidad de reglas registradas
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Indica si no hay reglas registradas
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Carga reglas desde un archivo de texto (ver `parse`)
+    pub fn load(&mut self, fname: &str) -> Result<(), String> {
+        let text =
+            fs::read_to_string(fname).map_err(|e| format!("couldn't read '{}': {}", fname, e))?;
+        self.parse(&text)
+    }
+
+    /// Agrega las reglas de un texto con una regla por línea:
+    ///
+    /// ```text
+    /// # comentario
+    /// redirect 301 /old/* /new/$1
+    /// rewrite /app/* /index.html
+    /// ```
+    pub fn parse(&mut self, text: &str) -> Result<(), String> {
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["redirect", code, pattern, target] => {
+                    let status = redirect_status(code).ok_or_else(|| {
+                        format!("line {}: invalid redirect status '{}'", i + 1, code)
+                    })?;
+                    self.redirect(pattern, target, status);
+                }
+                ["rewrite", pattern, target] => self.rewrite(pattern, target),
+                _ => return Err(format!("line {}: invalid rule '{}'", i + 1, line)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Aplica las reglas a la request. Las reescrituras cambian la ruta y la
+    /// evaluación sigue con la ruta nueva; la primera redirección que coincide
+    /// devuelve su response
+    pub fn apply(&self, req: &mut Request) -> Option<Response> {
+        for rule in &self.rules {
+            let captures = match wildcard_match(&rule.pattern, req.path.as_str()) {
+                Some(c) => c,
+                None => continue,
+            };
+            let mut target = substitute(&rule.target, &captures);
+
+            // Conserva la query original si el destino no define la suya
+            if !target.contains('?') {
+                if let Some((_, query)) = req.path.to_string().split_once('?') {
+                    target = format!("{}?{}", target, query);
+                }
+            }
+
+            match rule.action {
+                RuleAction::Redirect(status) => {
+                    return Some(Response::redirect_with(&target, status))
+                }
+                RuleAction::Rewrite => req.path = Path::from(target.as_str()),
+            }
+        }
+        None
+    }
+}
+
+// Convierte un código numérico en un StatusCode de redirección (301, 302, 303, 307 o 308)
+fn redirect_status(code: &str) -> Option<StatusCode> {
+    let status = StatusCode::from_u16(code.parse().ok()?)?;
+    is_redirect(status).then_some(status)
+}
+
+// Códigos que llevan un Location al que el cliente debe ir
+fn is_redirect(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 301..=303 | 307 | 308)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::Method;
+
+    fn request(path: &str) -> Request {
+        Request::new(Method::GET, path)
+    }
+
+    #[test]
+    // prueba de redirecciones con capturas y query
+    fn test_redirect() {
+        let mut rules = Rules::new();
+        rules.redirect("/", "/index.html", StatusCode::FOUND);
+        rules.redirect("/blog/*", "/posts/$1", StatusCode::PERMREDIRECT);
+
+        let res = rules.apply(&mut request("/")).unwrap();
+        assert_eq!(res.status, StatusCode::FOUND);
+        assert_eq!(
+            res.headers.get("Location"),
+            Some(&"/index.html".to_string())
+        );
+
+        let res = rules
+            .apply(&mut request("/blog/2024/hola?ref=rss"))
+            .unwrap();
+        assert_eq!(res.status, StatusCode::PERMREDIRECT);
+        assert_eq!(
+            res.headers.get("Location"),
+            Some(&"/posts/2024/hola?ref=rss".to_string())
+        );
+
+        assert!(rules.apply(&mut request("/about")).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid redirect status")]
+    // prueba de que una redirección con un código que no es 3xx se rechaza
+    fn test_redirect_invalid_status() {
+        let mut rules = Rules::new();
+        rules.redirect("/old", "/new", StatusCode::OK);
+    }
+
+    #[test]
+    // prueba de reescrituras internas
+    fn test_rewrite() {
+        let mut rules = Rules::new();
+        rules.rewrite("/app/*", "/index.html");
+        rules.rewrite("/u/*", "/users?name=$1");
+
+        let mut req = request("/app/settings/profile");
+        assert!(rules.apply(&mut req).is_none());
+        assert_eq!(req.path.as_str(), "/index.html");
+
+        let mut req = request("/u/ana");
+        assert!(rules.apply(&mut req).is_none());
+        assert_eq!(req.path.to_string(), "/users?name=ana");
+    }
+
+    #[test]
+    // prueba de carga de reglas desde texto
+    fn test_parse() {
+        let mut rules = Rules::new();
+        rules
+            .parse("# reglas\n\nredirect 303 /old/* /new/$1\nrewrite / /index.html\n")
+            .unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(
+            rules.rules[0],
+            Rule {
+                pattern: "/old/*".to_string(),
+                target: "/new/$1".to_string(),
+                action: RuleAction::Redirect(StatusCode::SEEOTHER),
+            }
+        );
+
+        assert_eq!(
+            Rules::new().parse("redirect 200 /a /b"),
+            Err("line 1: invalid redirect status '200'".to_string())
+        );
+        assert_eq!(
+            Rules::new().parse("\nmove /a /b"),
+            Err("line 2: invalid rule 'move /a /b'".to_string())
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use std::fs::File;
+use std::io::{self, Read, Write};
+
+#[cfg(target_os = "linux")]
+use std::os::unix::io::AsRawFd;
+
+// Máximo que acepta sendfile(2) por llamada en Linux
+#[cfg(target_os = "linux")]
+const MAX_SENDFILE: u64 = 0x7fff_f000;
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use std::io;
+    use std::os::raw::c_int;
+
+    extern "C" {
+        fn sendfile(out_fd: c_int, in_fd: c_int, offset: *mut i64, count: usize) -> isize;
+    }
+
+    // Sin offset, sendfile lee desde la posición actual del archivo y la avanza
+    pub fn send(out_fd: c_int, in_fd: c_int, count: usize) -> io::Result<usize> {
+        // SAFETY: sendfile solo usa los descriptores, que el llamador mantiene abiertos
+        // durante la llamada; con offset nulo no se lee ni escribe memoria del proceso
+        let n = unsafe { sendfile(out_fd, in_fd, std::ptr::null_mut(), count) };
+        if n < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(n as usize)
+        }
+    }
+}
+
+/// Copia `len` bytes de `file`, desde su posición actual, a `out`.
+/// En Linux usa `sendfile(2)`, que pasa los datos del archivo al socket sin copiarlos
+/// a memoria de usuario; si el sistema no lo admite, copia con un buffer
+#[cfg(target_os = "linux")]
+pub fn send_file<W: Write + AsRawFd>(file: &mut File, out: &mut W, len: u64) -> io::Result<()> {
+    let mut sent = 0;
+    while sent < len {
+        let count = (len - sent).min(MAX_SENDFILE) as usize;
+        match sys::send(out.as_raw_fd(), file.as_raw_fd(), count) {
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => sent += n as u64,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            // EINVAL/ENOSYS: el par de descriptores (o el kernel) no admite sendfile
+            Err(e)
+                if sent == 0
+                    && matches!(
+                        e.kind(),
+                        io::ErrorKind::InvalidInput | io::ErrorKind::Unsupported
+                    ) =>
+            {
+                return copy_buffered(file, out, len);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// Copia `len` bytes de `file`, desde su posición actual, a `out` con un buffer
+#[cfg(not(target_os = "linux"))]
+pub fn send_file<W: Write>(file: &mut File, out: &mut W, len: u64) -> io::Result<()> {
+    copy_buffered(file, out, len)
+}
+
+// Copia por bloques; falla si el archivo tiene menos bytes de los anunciados
+fn copy_buffered<W: Write>(file: &mut File, out: &mut W, len: u64) -> io::Result<()> {
+    let copied = io::copy(&mut file.take(len), out)?;
+    if copied < len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::io::{Seek, SeekFrom};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    #[test]
+    // prueba de envío de un archivo a un socket desde una posición dada
+    fn test_send_file() {
+        let path = env::temp_dir().join(format!("sendfile-{}", std::process::id()));
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        fs::write(&path, &data).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let reader = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = Vec::new();
+            stream.read_to_end(&mut buf).unwrap();
+            buf
+        });
+
+        let mut file = File::open(&path).unwrap();
+        file.seek(SeekFrom::Start(10)).unwrap();
+        let mut stream = TcpStream::connect(addr).unwrap();
+        send_file(&mut file, &mut stream, 90_000).unwrap();
+        drop(stream);
+        assert_eq!(reader.join().unwrap(), data[10..90_010].to_vec());
+
+        // un archivo más corto de lo anunciado es un error
+        let mut file = File::open(&path).unwrap();
+        let mut out = Vec::new();
+        assert!(copy_buffered(&mut file, &mut out, 200_000).is_err());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    // prueba de la copia con buffer cuando sendfile no admite el destino
+    fn test_send_file_fallback() {
+        let path = env::temp_dir().join(format!("sendfile-src-{}", std::process::id()));
+        let out_path = env::temp_dir().join(format!("sendfile-out-{}", std::process::id()));
+        fs::write(&path, "0123456789").unwrap();
+        fs::write(&out_path, "log:").unwrap();
+
+        // sendfile(2) rechaza con EINVAL un destino abierto en modo append
+        let mut file = File::open(&path).unwrap();
+        file.seek(SeekFrom::Start(2)).unwrap();
+        let mut out = File::options().append(true).open(&out_path).unwrap();
+        send_file(&mut file, &mut out, 5).unwrap();
+        assert_eq!(fs::read(&out_path).unwrap(), b"log:23456");
+
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&out_path).unwrap();
+    }
+}
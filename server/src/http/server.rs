@@ -1,15 +1,58 @@
-use super::{serve, Callback, Response, Router, StreamType};
+use super::{
+    serve, CacheStats, CookieEcho, ErrorFormat, FileCache, IntoResponse, Logger, Middleware, Next,
+    Request, Response, RouteInfo, Router, Rules, StatusCode, StreamType, TrailingSlash, Version,
+};
+use std::collections::HashMap;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener};
 use std::panic;
 use std::process;
 use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
 
-#[derive(Clone)]
-/// Representa un servidor HTTP
-pub struct Server {
+/// Manejador de errores: recibe la request y la response de error original
+pub type ErrorCallback = Arc<dyn Fn(&Request, &Response) -> Response + Send + Sync + 'static>;
+
+/// Representa un servidor HTTP. `S` es el tipo del estado compartido que
+/// reciben los manejadores registrados con `on_with_state`
+pub struct Server<S = ()> {
     addr: SocketAddr,
     router: Router,
+    // Routers por host virtual; el patrón puede ser "docs.local" o "*.example.com"
+    vhosts: Vec<(String, Router)>,
+    rules: Rules,
+    state: Arc<S>,
+    error_handlers: HashMap<StatusCode, ErrorCallback>,
+    error_format: ErrorFormat,
+    middlewares: Vec<Arc<dyn Middleware>>,
+    print_routes: bool,
+    // Verifica al iniciar `run` que existan los archivos registrados
+    check_files: bool,
+    // Tamaño máximo del cuerpo de las requests; las mayores reciben 413
+    max_body_size: Option<usize>,
+    // Valor del header `Server` agregado a cada response; None lo omite
+    server_header: Option<String>,
+    file_cache: Option<Arc<FileCache>>,
+}
+
+// Manual para no exigir `S: Clone`; el estado se comparte por `Arc`
+impl<S> Clone for Server<S> {
+    fn clone(&self) -> Self {
+        Self {
+            addr: self.addr,
+            router: self.router.clone(),
+            vhosts: self.vhosts.clone(),
+            rules: self.rules.clone(),
+            state: self.state.clone(),
+            error_handlers: self.error_handlers.clone(),
+            error_format: self.error_format,
+            middlewares: self.middlewares.clone(),
+            print_routes: self.print_routes,
+            check_files: self.check_files,
+            max_body_size: self.max_body_size,
+            server_header: self.server_header.clone(),
+            file_cache: self.file_cache.clone(),
+        }
+    }
 }
 
 impl Server {
@@ -21,11 +64,172 @@ impl Server {
                 port.parse::<u16>().unwrap(),
             ),
             router: Router::new(),
+            vhosts: Vec::new(),
+            rules: Rules::new(),
+            state: Arc::new(()),
+            error_handlers: HashMap::new(),
+            error_format: ErrorFormat::Text,
+            // Registro de accesos y eco de cookies activos por defecto
+            middlewares: vec![Arc::new(Logger), Arc::new(CookieEcho)],
+            print_routes: false,
+            check_files: false,
+            max_body_size: None,
+            server_header: Some(format!(
+                "{}/{}",
+                env!("CARGO_PKG_NAME"),
+                env!("CARGO_PKG_VERSION")
+            )),
+            file_cache: None,
+        }
+    }
+
+    /// Registra el estado compartido que recibirán los manejadores de `on_with_state`.
+    /// El tipo del estado pasa a ser parte del tipo del servidor
+    pub fn with_state<S: Send + Sync + 'static>(self, state: S) -> Server<S> {
+        Server {
+            addr: self.addr,
+            router: self.router,
+            vhosts: self.vhosts,
+            rules: self.rules,
+            state: Arc::new(state),
+            error_handlers: self.error_handlers,
+            error_format: self.error_format,
+            middlewares: self.middlewares,
+            print_routes: self.print_routes,
+            check_files: self.check_files,
+            max_body_size: self.max_body_size,
+            server_header: self.server_header,
+            file_cache: self.file_cache,
+        }
+    }
+}
+
+impl<S: Send + Sync + 'static> Server<S> {
+    /// Estado compartido, p. ej. para usarlo en los closures de un Router montado
+    pub fn state(&self) -> &Arc<S> {
+        &self.state
+    }
+
+    /// Procesa una request con los middlewares globales y devuelve la response correspondiente
+    pub fn handle(&self, req: &mut Request) -> Response {
+        let endpoint = |req: &mut Request| self.route(req);
+        let mut res = Next::new(&self.middlewares, &endpoint).run(req);
+
+        // Los cuerpos de largo desconocido van chunked si el cliente habla HTTP/1.1;
+        // HTTP/1.0 no entiende chunked, así que el fin lo marca el cierre de la conexión
+        match req.version {
+            Version::Http11 => {
+                if res.body.len().is_none() && res.headers.get("Content-Length").is_none() {
+                    res.set_chunked();
+                }
+            }
+            Version::Http10 => {
+                if res.is_chunked() {
+                    res.headers.remove("Transfer-Encoding");
+                }
+            }
+        }
+        if let Some(value) = &self.server_header {
+            if res.headers.get("Server").is_none() {
+                res.headers.insert("Server".to_string(), value.clone());
+            }
+        }
+        res
+    }
+
+    /// Aplica las reglas de reescritura, enruta la request y aplica los manejadores de error
+    fn route(&self, req: &mut Request) -> Response {
+        if let Some(max) = self.max_body_size {
+            let declared = req
+                .headers
+                .get("Content-Length")
+                .and_then(|len| len.trim().parse::<usize>().ok());
+            if declared.unwrap_or(0).max(req.body.len()) > max {
+                return self.error_response(req, Response::payload_too_large());
+            }
+        }
+        if let Some(res) = self.rules.apply(req) {
+            return res;
+        }
+        let mut res = self.select_router(req).handle_request(req);
+
+        // Los errores devueltos por los manejadores se muestran con el formato configurado
+        if let Some(err) = res.error.take() {
+            res = self.error_format.render(&err);
+        }
+
+        self.error_response(req, res)
+    }
+
+    // Reemplaza el cuerpo de las respuestas de error generadas por el servidor o por un
+    // `HttpError`; las responses que arma un manejador se envían tal cual
+    fn error_response(&self, req: &Request, res: Response) -> Response {
+        match self.error_handlers.get(&res.status) {
+            Some(handler) if !res.from_handler => {
+                let mut custom = handler(req, &res);
+                custom.status = res.status;
+                // Conserva los headers propios del error (Allow, WWW-Authenticate, Retry-After...)
+                for (key, value) in res.headers.iter() {
+                    let body_header = key.eq_ignore_ascii_case("Content-Type")
+                        || key.eq_ignore_ascii_case("Content-Length");
+                    if !body_header && custom.headers.get(key).is_none() {
+                        custom.headers.insert(key.clone(), value.clone());
+                    }
+                }
+                custom
+            }
+            _ => res,
         }
     }
 
-    /// Inicia el servidor con un número específico de hilos
-    pub fn run(&self, no_threads: usize) {
+    /// Elige el Router según el header `Host`: primero coincidencias exactas,
+    /// luego el comodín más específico y por último el Router principal
+    fn select_router(&self, req: &Request) -> &Router {
+        let host = match req.headers.get("Host") {
+            Some(h) => normalize_host(h),
+            None => return &self.router,
+        };
+
+        if let Some((_, router)) = self.vhosts.iter().find(|(pat, _)| *pat == host) {
+            return router;
+        }
+        self.vhosts
+            .iter()
+            .filter(|(pat, _)| {
+                pat.strip_prefix("*.")
+                    .is_some_and(|domain| host.ends_with(&format!(".{}", domain)))
+            })
+            .max_by_key(|(pat, _)| pat.len())
+            .map_or(&self.router, |(_, router)| router)
+    }
+
+    /// Inicia el servidor con un número específico de hilos.
+    /// Devuelve un error si no puede empezar a aceptar conexiones
+    pub fn run(&self, no_threads: usize) -> Result<(), String> {
+        // Verifica que todos los archivos registrados existan antes de aceptar conexiones
+        if self.check_files {
+            let missing = self.missing_files();
+            if !missing.is_empty() {
+                return Err(format!(
+                    "registered files do not exist: {}",
+                    missing.join(", ")
+                ));
+            }
+        }
+
+        if self.print_routes {
+            println!("Routes:");
+            for route in self.routes() {
+                println!("  {}", route);
+            }
+            for (host, router) in &self.vhosts {
+                println!("Routes for host {}:", host);
+                for route in router.routes() {
+                    println!("  {}", route);
+                }
+            }
+        }
+
         // Configura un manejador de pánico para terminar el proceso si un hilo entra en pánico
         let orig_hook = panic::take_hook();
         panic::set_hook(Box::new(move |panic_info| {
@@ -35,8 +239,10 @@ impl Server {
         }));
 
         // Crea el listener TCP y lo envuelve en un Arc<Mutex>
-        let listener = Arc::new(Mutex::new(TcpListener::bind(self.addr).unwrap()));
-        let router = Arc::new(self.router.clone());
+        let listener = TcpListener::bind(self.addr)
+            .map_err(|e| format!("couldn't bind {}: {}", self.addr, e))?;
+        let listener = Arc::new(Mutex::new(listener));
+        let server = Arc::new(self.clone());
         println!(
             "Listening on http://{} with {} threads.",
             self.addr, no_threads
@@ -50,7 +256,7 @@ impl Server {
             let thread_name = format!("Thread {}", id);
             let thread_tx = tx.clone();
             let listener_shared = Arc::clone(&listener);
-            let router_shared = Arc::clone(&router);
+            let server_shared = Arc::clone(&server);
 
             children.push(
                 thread::Builder::new()
@@ -60,7 +266,7 @@ impl Server {
                         {
                             stream = listener_shared.lock().unwrap().accept();
                         }
-                        let ans = serve(&thread_name, &router_shared, stream);
+                        let ans = serve(&server_shared, stream);
                         thread_tx.send(ans).unwrap();
                     })
                     .unwrap(),
@@ -76,23 +282,215 @@ impl Server {
                 }
             }
         }
+        Ok(())
     }
 
-    /// Registra un callback para una ruta específica
-    pub fn on(&mut self, pat: &str, cb: Callback) {
+    /// Registra un callback (función o closure) para una ruta específica.
+    /// El callback puede devolver `Response` o `Result<Response, HttpError>`
+    pub fn on<F, R>(&mut self, pat: &str, cb: F)
+    where
+        F: Fn(&Request) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
         self.router.insert_callback(pat, cb);
     }
 
+    /// Registra un callback que recibe el estado compartido del servidor
+    pub fn on_with_state<F, R>(&mut self, pat: &str, cb: F)
+    where
+        F: Fn(&Request, &S) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        let state = self.state.clone();
+        self.router
+            .insert_callback(pat, move |req: &Request| cb(req, &state));
+    }
+
+    /// Registra un manejador para las rutas no encontradas (404)
+    pub fn on_not_found<F, R>(&mut self, cb: F)
+    where
+        F: Fn(&Request) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.on_error(StatusCode::NOTFOUND, move |req, _res| {
+            cb(req).into_response()
+        });
+    }
+
+    /// Configura el formato (texto, HTML o JSON) de los `HttpError` devueltos por los manejadores
+    pub fn error_format(&mut self, format: ErrorFormat) {
+        self.error_format = format;
+    }
+
+    /// Registra un manejador para las respuestas de error con el código de estado dado.
+    /// Se aplica a los errores del servidor y a los `HttpError`, no a las responses que
+    /// arma un manejador. La response generada conserva el código de estado original
+    /// y los headers del error salvo Content-Type y Content-Length
+    pub fn on_error<F>(&mut self, status: StatusCode, cb: F)
+    where
+        F: Fn(&Request, &Response) -> Response + Send + Sync + 'static,
+    {
+        self.error_handlers.insert(status, Arc::new(cb));
+    }
+
+    /// Devuelve la tabla de rutas registradas
+    pub fn routes(&self) -> Vec<RouteInfo> {
+        self.router.routes()
+    }
+
+    /// Limita el tamaño del cuerpo de las requests; las que lo superan reciben 413
+    pub fn max_body_size(&mut self, bytes: usize) {
+        self.max_body_size = Some(bytes);
+    }
+
+    /// Archivos y directorios registrados, también en los hosts virtuales, que no existen en disco
+    pub fn missing_files(&self) -> Vec<String> {
+        std::iter::once(&self.router)
+            .chain(self.vhosts.iter().map(|(_, router)| router))
+            .flat_map(|router| router.missing_files())
+            .collect()
+    }
+
+    /// Hace que `run` falle sin aceptar conexiones si falta algún archivo registrado
+    pub fn check_files(&mut self, enabled: bool) {
+        self.check_files = enabled;
+    }
+
+    /// Imprime la tabla de rutas al iniciar `run`
+    pub fn print_routes(&mut self, enabled: bool) {
+        self.print_routes = enabled;
+    }
+
+    /// Asigna un nombre descriptivo a una ruta ya registrada
+    pub fn name(&mut self, pat: &str, name: &str) {
+        self.router.set_name(pat, name);
+    }
+
+    /// Configura la política para la '/' final de las rutas del Router principal.
+    /// Los sub-routers montados usan su propia política (`Router::set_trailing_slash`)
+    pub fn trailing_slash(&mut self, policy: TrailingSlash) {
+        self.router.set_trailing_slash(policy);
+    }
+
+    /// Asigna `Cache-Control` a los archivos del Router principal cuya ruta coincide
+    /// con `pattern`, p. ej. `/assets/*` con `public, max-age=31536000, immutable`
+    pub fn cache_control(&mut self, pattern: &str, value: &str) {
+        self.router.set_cache_control(pattern, value);
+    }
+
+    /// Asigna `Cache-Control` a los archivos del Router principal según su tipo MIME,
+    /// p. ej. `text/html` con `no-cache`
+    pub fn cache_control_mime(&mut self, mime: &str, value: &str) {
+        self.router.set_cache_control_mime(mime, value);
+    }
+
+    /// Activa una caché en memoria de hasta `capacity` bytes para las rutas de archivo
+    /// (`on_file`) de todos los routers. Devuelve la caché para consultar sus estadísticas
+    pub fn file_cache(&mut self, capacity: u64) -> Arc<FileCache> {
+        let cache = Arc::new(FileCache::new(capacity));
+        self.router.set_file_cache(cache.clone());
+        for (_, router) in &mut self.vhosts {
+            router.set_file_cache(cache.clone());
+        }
+        self.file_cache = Some(cache.clone());
+        cache
+    }
+
+    /// Estadísticas de la caché de archivos, si está activa
+    pub fn file_cache_stats(&self) -> Option<CacheStats> {
+        self.file_cache.as_ref().map(|cache| cache.stats())
+    }
+
+    /// Configura el header `Server` de las responses (por defecto "server/<versión>");
+    /// con `None` no se envía
+    pub fn server_header(&mut self, value: Option<&str>) {
+        self.server_header = value.map(str::to_string);
+    }
+
+    /// Agrega un middleware global, que envuelve todas las requests
+    pub fn middleware<M: Middleware + 'static>(&mut self, middleware: M) {
+        self.middlewares.push(Arc::new(middleware));
+    }
+
+    /// Quita los middlewares globales, incluidos `Logger` y `CookieEcho` que se
+    /// registran por defecto; los que se agreguen después siguen funcionando
+    pub fn clear_middlewares(&mut self) {
+        self.middlewares.clear();
+    }
+
+    /// Agrega un middleware que solo se ejecuta para una ruta ya registrada
+    pub fn route_middleware<M: Middleware + 'static>(&mut self, pat: &str, middleware: M) {
+        self.router.insert_route_middleware(pat, middleware);
+    }
+
+    /// Redirige las rutas que coinciden con `pattern` ('*' captura, `$1` sustituye)
+    /// con un código 3xx; otro código termina en pánico
+    pub fn redirect(&mut self, pattern: &str, target: &str, status: StatusCode) {
+        self.rules.redirect(pattern, target, status);
+    }
+
+    /// Reescribe internamente las rutas que coinciden con `pattern` antes de enrutarlas
+    pub fn rewrite(&mut self, pattern: &str, target: &str) {
+        self.rules.rewrite(pattern, target);
+    }
+
+    /// Carga reglas de redirección y reescritura desde un archivo
+    pub fn load_rules(&mut self, fname: &str) -> Result<(), String> {
+        self.rules.load(fname)
+    }
+
+    /// Atiende con `router` las requests cuyo header `Host` coincida con `host`.
+    /// Acepta comodines de subdominio ("*.example.com"); el puerto se ignora
+    pub fn vhost(&mut self, host: &str, mut router: Router) {
+        if let Some(cache) = &self.file_cache {
+            router.set_file_cache(cache.clone());
+        }
+        self.vhosts.push((normalize_host(host), router));
+    }
+
+    /// Monta un Router independiente bajo un prefijo de ruta.
+    /// Los manejadores internos reciben la ruta sin el prefijo (disponible en `req.path.base()`)
+    pub fn mount(&mut self, prefix: &str, router: Router) {
+        self.router.mount(prefix, router);
+    }
+
     /// Registra un archivo para ser servido en una ruta específica
     pub fn on_file(&mut self, pat: &str, fname: &str) {
         self.router.insert_file(pat, fname);
     }
+
+    /// Sirve los archivos de un directorio bajo un prefijo de ruta.
+    /// Los directorios sirven su `index.html` y se rechazan las rutas fuera de `root`
+    pub fn on_dir(&mut self, pat: &str, root: &str) {
+        self.router.insert_dir(pat, root, false);
+    }
+
+    /// Igual que `on_dir`, pero los directorios sin `index.html` muestran un listado
+    /// ordenable en HTML, o en JSON si la request envía `Accept: application/json`
+    pub fn on_dir_listing(&mut self, pat: &str, root: &str) {
+        self.router.insert_dir(pat, root, true);
+    }
+}
+
+// Normaliza un host para compararlo: minúsculas, sin puerto ni punto final
+fn normalize_host(host: &str) -> String {
+    let host = host.trim();
+    let host = match host.strip_prefix('[') {
+        // IPv6: "[::1]:8080" -> "[::1]"
+        Some(rest) => rest
+            .split_once(']')
+            .map_or(host, |(ip, _)| &host[..ip.len() + 2]),
+        None => host.split_once(':').map_or(host, |(name, _)| name),
+    };
+    host.trim_end_matches('.').to_ascii_lowercase()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::http::{Headers, HttpError, Method, Version};
     use std::net::SocketAddr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     #[test]
     // prueba de creación de un servidor
@@ -136,4 +534,385 @@ mod tests {
         assert!(server.router.has_route("/test"));
         assert!(server.router.has_route("/another"));
     }
+
+    #[test]
+    // prueba de la verificación de archivos registrados al iniciar
+    fn test_server_check_files() {
+        let mut server = Server::new("127.0.0.1", "0");
+        server.on_file("/cargo", "Cargo.toml");
+        let mut docs = Router::new();
+        docs.insert_file("/", "./static/missing.html");
+        server.vhost("docs.local", docs);
+        assert_eq!(
+            server.missing_files(),
+            vec!["./static/missing.html".to_string()]
+        );
+
+        // con la verificación activa, run falla antes de aceptar conexiones
+        server.check_files(true);
+        let err = server.run(1).unwrap_err();
+        assert!(err.contains("./static/missing.html"));
+    }
+
+    #[test]
+    // prueba de estado compartido entre manejadores
+    fn test_server_with_state() {
+        struct AppState {
+            visits: AtomicUsize,
+        }
+
+        let mut server = Server::new("127.0.0.1", "8080").with_state(AppState {
+            visits: AtomicUsize::new(0),
+        });
+        server.on_with_state("/visits", |_req, state: &AppState| {
+            let n = state.visits.fetch_add(1, Ordering::SeqCst) + 1;
+            Response::ok(&n.to_string())
+        });
+        // un Router montado accede al mismo estado a través de `state()`
+        let state = server.state().clone();
+        let mut api = Router::new();
+        api.insert_callback("/visits", move |_req: &Request| {
+            Response::ok(&state.visits.load(Ordering::SeqCst).to_string())
+        });
+        server.mount("/api", api);
+
+        for (path, expected) in [("/visits", "1"), ("/visits", "2"), ("/api/visits", "2")] {
+            let mut req = Request::new(Method::GET, path);
+            let res = server.handle(&mut req);
+            assert_eq!(
+                String::from_utf8_lossy(res.body.as_bytes().unwrap()),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    // prueba de montaje de un sub-router
+    fn test_server_mount() {
+        let mut api = Router::new();
+        api.insert_callback("/ping", |_req: &Request| Response::ok("pong"));
+
+        let mut server = Server::new("127.0.0.1", "8080");
+        server.mount("/api", api);
+        assert!(server.router.has_mount("/api"));
+        assert_eq!(server.router.route_count(), 0);
+    }
+
+    #[test]
+    // prueba de manejadores de error personalizados
+    fn test_server_error_handlers() {
+        let mut server = Server::new("127.0.0.1", "8080");
+        server.on("/fail", |_req| -> Result<Response, HttpError> {
+            Err(HttpError::internal("db down"))
+        });
+        server.on("/own", |_req| Response::internal_err("handled"));
+        server.on_not_found(|req| Response::ok(&format!("<h1>{} not here</h1>", req.path)));
+        server.on_error(StatusCode::INTERNALERR, |_req, res| {
+            Response::ok(&format!(
+                "{{\"error\":\"{}\"}}",
+                String::from_utf8_lossy(res.body.as_bytes().unwrap())
+            ))
+        });
+
+        let mut req = Request::new(Method::GET, "/missing");
+        let res = server.handle(&mut req);
+        assert_eq!(res.status, StatusCode::NOTFOUND);
+        assert_eq!(
+            String::from_utf8_lossy(res.body.as_bytes().unwrap()),
+            "<h1>/missing not here</h1>"
+        );
+
+        req.path = "/fail".into();
+        let res = server.handle(&mut req);
+        assert_eq!(res.status, StatusCode::INTERNALERR);
+        assert_eq!(
+            String::from_utf8_lossy(res.body.as_bytes().unwrap()),
+            "{\"error\":\"db down\"}"
+        );
+
+        // las responses de error que arma un manejador no se reemplazan
+        req.path = "/own".into();
+        let res = server.handle(&mut req);
+        assert_eq!(res.status, StatusCode::INTERNALERR);
+        assert_eq!(res.body.as_bytes().unwrap(), b"handled");
+    }
+
+    #[test]
+    // prueba de que el manejador de error conserva los headers del error original
+    fn test_server_error_handler_headers() {
+        let mut server = Server::new("127.0.0.1", "8080");
+        server.on_error(StatusCode::UNAUTHORIZED, |_req, _res| {
+            let mut res = Response::ok("<h1>Login required</h1>");
+            res.headers
+                .insert("Content-Type".to_string(), "text/html".to_string());
+            res
+        });
+
+        let req = Request::new(Method::GET, "/admin");
+        let res = server.error_response(&req, Response::unauthorized("admin"));
+        assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(res.body.as_bytes().unwrap(), b"<h1>Login required</h1>");
+        assert_eq!(
+            res.headers.get("WWW-Authenticate"),
+            Some(&"Basic realm=\"admin\"".to_string())
+        );
+        assert_eq!(
+            res.headers.get("Content-Type"),
+            Some(&"text/html".to_string())
+        );
+    }
+
+    #[test]
+    // prueba del límite de tamaño del cuerpo de las requests
+    fn test_server_max_body_size() {
+        let mut server = Server::new("127.0.0.1", "8080");
+        server.on("/upload", |req| Response::ok(&req.body));
+        server.max_body_size(8);
+        server.on_error(StatusCode::PAYLOADTOOLARGE, |_req, _res| {
+            Response::ok("too big")
+        });
+
+        let mut req = Request {
+            body: "12345678".to_string(),
+            ..Request::new(Method::POST, "/upload")
+        };
+        assert_eq!(server.handle(&mut req).status, StatusCode::OK);
+
+        req.body = "123456789".to_string();
+        let res = server.handle(&mut req);
+        assert_eq!(res.status, StatusCode::PAYLOADTOOLARGE);
+        assert_eq!(res.body.as_bytes().unwrap(), b"too big");
+
+        // el Content-Length declarado cuenta aunque el cuerpo leído sea menor
+        let mut req = Request {
+            headers: Headers::from(&vec![("Content-Length", "4096")]),
+            ..Request::new(Method::POST, "/upload")
+        };
+        assert_eq!(server.handle(&mut req).status, StatusCode::PAYLOADTOOLARGE);
+    }
+
+    #[test]
+    // prueba de la eliminación de los middlewares por defecto
+    fn test_server_clear_middlewares() {
+        let mut server = Server::new("127.0.0.1", "8080");
+        server.on("/", |_req| Response::ok("home"));
+        server.clear_middlewares();
+
+        let mut req = Request {
+            headers: Headers::from(&vec![("Cookie", "theme=dark")]),
+            ..Request::new(Method::GET, "/")
+        };
+        let res = server.handle(&mut req);
+        assert_eq!(res.status, StatusCode::OK);
+        assert!(res.headers.get("Set-Cookie").is_none());
+    }
+
+    #[test]
+    // prueba de middlewares globales y de cookies por defecto
+    fn test_server_middleware() {
+        struct PoweredBy;
+        impl Middleware for PoweredBy {
+            fn after(&self, _req: &Request, res: &mut Response) {
+                res.headers
+                    .insert("X-Powered-By".to_string(), "rust".to_string());
+            }
+        }
+
+        let mut server = Server::new("127.0.0.1", "8080");
+        server.on("/", |_req| Response::ok("home"));
+        server.middleware(PoweredBy);
+
+        let mut req = Request {
+            headers: Headers::from(&vec![("Cookie", "theme=dark")]),
+            ..Request::new(Method::GET, "/missing")
+        };
+        let res = server.handle(&mut req);
+        assert_eq!(res.status, StatusCode::NOTFOUND);
+        assert_eq!(res.headers.get("X-Powered-By"), Some(&"rust".to_string()));
+        assert_eq!(
+            res.headers.get("Set-Cookie"),
+            Some(&"theme=dark".to_string())
+        );
+        assert_eq!(
+            res.headers.get("Server"),
+            Some(&format!("server/{}", env!("CARGO_PKG_VERSION")))
+        );
+
+        // el header Server es configurable y puede omitirse
+        server.server_header(Some("demo"));
+        assert_eq!(
+            server.handle(&mut req).headers.get("Server"),
+            Some(&"demo".to_string())
+        );
+        server.server_header(None);
+        assert!(server.handle(&mut req).headers.get("Server").is_none());
+    }
+
+    #[test]
+    // prueba de normalización de hosts
+    fn test_normalize_host() {
+        assert_eq!(normalize_host("Docs.Local:8080"), "docs.local");
+        assert_eq!(normalize_host("example.com."), "example.com");
+        assert_eq!(normalize_host("[::1]:7878"), "[::1]");
+        assert_eq!(normalize_host("[::1]"), "[::1]");
+    }
+
+    #[test]
+    // prueba de selección de Router por host virtual
+    fn test_server_vhost() {
+        let site = |name: &'static str| {
+            let mut router = Router::new();
+            router.insert_callback("/", move |_req: &Request| Response::ok(name));
+            router
+        };
+
+        let mut server = Server::new("127.0.0.1", "8080");
+        server.on("/", |_req| Response::ok("default"));
+        server.vhost("docs.local", site("docs"));
+        server.vhost("*.example.com", site("wildcard"));
+        server.vhost("*.api.example.com", site("api"));
+
+        let body = |host: Option<&str>| {
+            let headers = match host {
+                Some(h) => Headers::from(&vec![("Host", h)]),
+                None => Headers::from(&vec![]),
+            };
+            let mut req = Request {
+                headers,
+                ..Request::new(Method::GET, "/")
+            };
+            String::from_utf8(server.handle(&mut req).body.into_bytes().unwrap()).unwrap()
+        };
+
+        assert_eq!(body(Some("DOCS.local:8080")), "docs");
+        assert_eq!(body(Some("www.example.com")), "wildcard");
+        assert_eq!(body(Some("v1.api.example.com")), "api");
+        assert_eq!(body(Some("example.com")), "default");
+        assert_eq!(body(Some("other.local")), "default");
+        assert_eq!(body(None), "default");
+    }
+
+    #[test]
+    // prueba de reglas de redirección y reescritura antes del enrutamiento
+    fn test_server_rules() {
+        let mut server = Server::new("127.0.0.1", "8080");
+        server.on("/index.html", |req| Response::ok(&req.path.to_string()));
+        server.redirect("/", "/index.html", StatusCode::TEMPREDIRECT);
+        server.rewrite("/app/*", "/index.html?page=$1");
+
+        let mut req = Request::new(Method::GET, "/");
+        let res = server.handle(&mut req);
+        assert_eq!(res.status, StatusCode::TEMPREDIRECT);
+        assert_eq!(
+            res.headers.get("Location"),
+            Some(&"/index.html".to_string())
+        );
+
+        req.path = "/app/settings".into();
+        let res = server.handle(&mut req);
+        assert_eq!(
+            String::from_utf8(res.body.into_bytes().unwrap()).unwrap(),
+            "/index.html?page=settings"
+        );
+    }
+
+    #[test]
+    // prueba de manejadores que devuelven Result con HttpError
+    fn test_server_fallible_handler() {
+        use crate::http::{parse_url_param, HttpError};
+
+        let mut server = Server::new("127.0.0.1", "8080");
+        server.error_format(ErrorFormat::Json);
+        server.on("/api", |req| -> Result<Response, HttpError> {
+            let body = parse_url_param(&req.body)?;
+            let name = body
+                .get("name")
+                .ok_or_else(|| HttpError::bad_request("Missing name"))?;
+            Ok(Response::ok(name))
+        });
+
+        let mut req = Request {
+            body: "name=ana".to_string(),
+            ..Request::new(Method::POST, "/api")
+        };
+        assert_eq!(server.handle(&mut req).body, b"ana");
+
+        req.body = "other=1".to_string();
+        let res = server.handle(&mut req);
+        assert_eq!(res.status, StatusCode::BADREQUEST);
+        assert_eq!(res.body, b"{\"status\":400,\"error\":\"Missing name\"}");
+        assert!(res.error.is_none());
+    }
+
+    #[test]
+    // prueba de chunked automático según la versión del cliente
+    fn test_server_auto_chunked() {
+        use crate::http::Body;
+
+        let mut server = Server::new("127.0.0.1", "8080");
+        server.on("/report", |_req| {
+            Response::builder().body(Body::iter(vec![b"fila\n".to_vec()].into_iter()))
+        });
+        server.on("/small", |_req| Response::ok("ok"));
+
+        let mut req = Request::new(Method::GET, "/report");
+        assert!(server.handle(&mut req).is_chunked());
+
+        req.version = Version::Http10;
+        assert!(!server.handle(&mut req).is_chunked());
+
+        req.version = Version::Http11;
+        req.path = "/small".into();
+        assert!(!server.handle(&mut req).is_chunked());
+    }
+
+    #[test]
+    // prueba de que la compresión vuelve débil el ETag del archivo
+    fn test_server_compression_etag() {
+        use crate::http::Compression;
+
+        let mut server = Server::new("127.0.0.1", "8080");
+        server.on_file("/", "./static/index.html");
+        server.middleware(Compression::new());
+
+        let mut req = Request::new(Method::GET, "/");
+        let etag = server.handle(&mut req).headers.get("ETag").unwrap().clone();
+        assert!(etag.starts_with('"'));
+
+        req.headers = Headers::from(&vec![("Accept-Encoding", "gzip")]);
+        let res = server.handle(&mut req);
+        assert_eq!(
+            res.headers.get("Content-Encoding"),
+            Some(&"gzip".to_string())
+        );
+        let weak = format!("W/{}", etag);
+        assert_eq!(res.headers.get("ETag"), Some(&weak));
+
+        // el ETag débil sigue validando la versión comprimida
+        req.headers = Headers::from(&vec![("Accept-Encoding", "gzip"), ("If-None-Match", &weak)]);
+        assert_eq!(server.handle(&mut req).status, StatusCode::NOTMODIFIED);
+    }
+
+    #[test]
+    // prueba de la caché de archivos compartida por los routers
+    fn test_server_file_cache() {
+        let mut server = Server::new("127.0.0.1", "8080");
+        assert!(server.file_cache_stats().is_none());
+        server.on_file("/cargo", "Cargo.toml");
+        let mut api = Router::new();
+        api.insert_file("/manifest", "Cargo.toml");
+        server.mount("/api", api);
+        server.file_cache(1 << 20);
+
+        let mut req = Request::new(Method::GET, "/cargo");
+        let expected = std::fs::read("Cargo.toml").unwrap();
+        assert_eq!(server.handle(&mut req).body.into_bytes().unwrap(), expected);
+        assert_eq!(server.handle(&mut req).body.into_bytes().unwrap(), expected);
+        req.path = "/api/manifest".into();
+        assert_eq!(server.handle(&mut req).body.into_bytes().unwrap(), expected);
+
+        let stats = server.file_cache_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses), (2, 1));
+        assert_eq!(stats.entries, 1);
+    }
 }
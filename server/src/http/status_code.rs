@@ -1,9 +1,237 @@
-#[allow(dead_code)]
-#[derive(Debug, Copy, Clone, PartialEq)]
-pub enum StatusCode {
-    OK = 200,
-    REDIRECT = 301,
-    UNAVAILABLE = 503,
-    INTERNALERR = 500,
-    NOTFOUND = 404,
+use std::convert::TryFrom;
+use std::fmt;
+
+/// Código de estado HTTP. Incluye constantes para todo el registro de IANA y
+/// admite cualquier otro código numérico de tres dígitos
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct StatusCode(u16);
+
+impl StatusCode {
+    // Informativos (1xx)
+    pub const CONTINUE: StatusCode = StatusCode(100);
+    pub const SWITCHINGPROTOCOLS: StatusCode = StatusCode(101);
+    pub const PROCESSING: StatusCode = StatusCode(102);
+    pub const EARLYHINTS: StatusCode = StatusCode(103);
+
+    // Éxito (2xx)
+    pub const OK: StatusCode = StatusCode(200);
+    pub const CREATED: StatusCode = StatusCode(201);
+    pub const ACCEPTED: StatusCode = StatusCode(202);
+    pub const NONAUTHORITATIVE: StatusCode = StatusCode(203);
+    pub const NOCONTENT: StatusCode = StatusCode(204);
+    pub const RESETCONTENT: StatusCode = StatusCode(205);
+    pub const PARTIALCONTENT: StatusCode = StatusCode(206);
+    pub const MULTISTATUS: StatusCode = StatusCode(207);
+    pub const ALREADYREPORTED: StatusCode = StatusCode(208);
+    pub const IMUSED: StatusCode = StatusCode(226);
+
+    // Redirecciones (3xx)
+    pub const MULTIPLECHOICES: StatusCode = StatusCode(300);
+    pub const REDIRECT: StatusCode = StatusCode(301);
+    pub const FOUND: StatusCode = StatusCode(302);
+    pub const SEEOTHER: StatusCode = StatusCode(303);
+    pub const NOTMODIFIED: StatusCode = StatusCode(304);
+    pub const USEPROXY: StatusCode = StatusCode(305);
+    pub const TEMPREDIRECT: StatusCode = StatusCode(307);
+    pub const PERMREDIRECT: StatusCode = StatusCode(308);
+
+    // Errores del cliente (4xx)
+    pub const BADREQUEST: StatusCode = StatusCode(400);
+    pub const UNAUTHORIZED: StatusCode = StatusCode(401);
+    pub const PAYMENTREQUIRED: StatusCode = StatusCode(402);
+    pub const FORBIDDEN: StatusCode = StatusCode(403);
+    pub const NOTFOUND: StatusCode = StatusCode(404);
+    pub const METHODNOTALLOWED: StatusCode = StatusCode(405);
+    pub const NOTACCEPTABLE: StatusCode = StatusCode(406);
+    pub const PROXYAUTHREQUIRED: StatusCode = StatusCode(407);
+    pub const REQUESTTIMEOUT: StatusCode = StatusCode(408);
+    pub const CONFLICT: StatusCode = StatusCode(409);
+    pub const GONE: StatusCode = StatusCode(410);
+    pub const LENGTHREQUIRED: StatusCode = StatusCode(411);
+    pub const PRECONDITIONFAILED: StatusCode = StatusCode(412);
+    pub const PAYLOADTOOLARGE: StatusCode = StatusCode(413);
+    pub const URITOOLONG: StatusCode = StatusCode(414);
+    pub const UNSUPPORTEDMEDIATYPE: StatusCode = StatusCode(415);
+    pub const RANGENOTSATISFIABLE: StatusCode = StatusCode(416);
+    pub const EXPECTATIONFAILED: StatusCode = StatusCode(417);
+    pub const MISDIRECTEDREQUEST: StatusCode = StatusCode(421);
+    pub const UNPROCESSABLE: StatusCode = StatusCode(422);
+    pub const LOCKED: StatusCode = StatusCode(423);
+    pub const FAILEDDEPENDENCY: StatusCode = StatusCode(424);
+    pub const TOOEARLY: StatusCode = StatusCode(425);
+    pub const UPGRADEREQUIRED: StatusCode = StatusCode(426);
+    pub const PRECONDITIONREQUIRED: StatusCode = StatusCode(428);
+    pub const TOOMANYREQUESTS: StatusCode = StatusCode(429);
+    pub const HEADERSTOOLARGE: StatusCode = StatusCode(431);
+    pub const LEGALREASONS: StatusCode = StatusCode(451);
+
+    // Errores del servidor (5xx)
+    pub const INTERNALERR: StatusCode = StatusCode(500);
+    pub const NOTIMPLEMENTED: StatusCode = StatusCode(501);
+    pub const BADGATEWAY: StatusCode = StatusCode(502);
+    pub const UNAVAILABLE: StatusCode = StatusCode(503);
+    pub const GATEWAYTIMEOUT: StatusCode = StatusCode(504);
+    pub const VERSIONNOTSUPPORTED: StatusCode = StatusCode(505);
+    pub const VARIANTALSONEGOTIATES: StatusCode = StatusCode(506);
+    pub const INSUFFICIENTSTORAGE: StatusCode = StatusCode(507);
+    pub const LOOPDETECTED: StatusCode = StatusCode(508);
+    pub const NOTEXTENDED: StatusCode = StatusCode(510);
+    pub const NETWORKAUTHREQUIRED: StatusCode = StatusCode(511);
+
+    /// Crea un código a partir de su valor numérico (100 a 599)
+    pub fn from_u16(code: u16) -> Option<StatusCode> {
+        (100..=599).contains(&code).then_some(StatusCode(code))
+    }
+
+    /// Valor numérico del código
+    pub fn as_u16(&self) -> u16 {
+        self.0
+    }
+
+    /// Frase de razón estándar; vacía para los códigos no registrados
+    pub fn reason_phrase(&self) -> &'static str {
+        match self.0 {
+            100 => "Continue",
+            101 => "Switching Protocols",
+            102 => "Processing",
+            103 => "Early Hints",
+            200 => "OK",
+            201 => "Created",
+            202 => "Accepted",
+            203 => "Non-Authoritative Information",
+            204 => "No Content",
+            205 => "Reset Content",
+            206 => "Partial Content",
+            207 => "Multi-Status",
+            208 => "Already Reported",
+            226 => "IM Used",
+            300 => "Multiple Choices",
+            301 => "Moved Permanently",
+            302 => "Found",
+            303 => "See Other",
+            304 => "Not Modified",
+            305 => "Use Proxy",
+            307 => "Temporary Redirect",
+            308 => "Permanent Redirect",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            402 => "Payment Required",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            406 => "Not Acceptable",
+            407 => "Proxy Authentication Required",
+            408 => "Request Timeout",
+            409 => "Conflict",
+            410 => "Gone",
+            411 => "Length Required",
+            412 => "Precondition Failed",
+            413 => "Content Too Large",
+            414 => "URI Too Long",
+            415 => "Unsupported Media Type",
+            416 => "Range Not Satisfiable",
+            417 => "Expectation Failed",
+            421 => "Misdirected Request",
+            422 => "Unprocessable Content",
+            423 => "Locked",
+            424 => "Failed Dependency",
+            425 => "Too Early",
+            426 => "Upgrade Required",
+            428 => "Precondition Required",
+            429 => "Too Many Requests",
+            431 => "Request Header Fields Too Large",
+            451 => "Unavailable For Legal Reasons",
+            500 => "Internal Server Error",
+            501 => "Not Implemented",
+            502 => "Bad Gateway",
+            503 => "Service Unavailable",
+            504 => "Gateway Timeout",
+            505 => "HTTP Version Not Supported",
+            506 => "Variant Also Negotiates",
+            507 => "Insufficient Storage",
+            508 => "Loop Detected",
+            510 => "Not Extended",
+            511 => "Network Authentication Required",
+            _ => "",
+        }
+    }
+
+    /// 1xx
+    pub fn is_informational(&self) -> bool {
+        (100..200).contains(&self.0)
+    }
+
+    /// 2xx
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.0)
+    }
+
+    /// 3xx
+    pub fn is_redirection(&self) -> bool {
+        (300..400).contains(&self.0)
+    }
+
+    /// 4xx
+    pub fn is_client_error(&self) -> bool {
+        (400..500).contains(&self.0)
+    }
+
+    /// 5xx
+    pub fn is_server_error(&self) -> bool {
+        (500..600).contains(&self.0)
+    }
+}
+
+impl TryFrom<u16> for StatusCode {
+    type Error = &'static str;
+
+    fn try_from(code: u16) -> Result<Self, Self::Error> {
+        StatusCode::from_u16(code).ok_or("Invalid status code")
+    }
+}
+
+// Print del código con su frase de razón, p. ej. "404 Not Found"
+impl fmt::Display for StatusCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.0, self.reason_phrase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    // prueba de frases de razón y formato
+    fn test_reason_phrase() {
+        assert_eq!(StatusCode::OK.reason_phrase(), "OK");
+        assert_eq!(StatusCode::NOTFOUND.to_string(), "404 Not Found");
+        assert_eq!(StatusCode::REDIRECT.to_string(), "301 Moved Permanently");
+        assert_eq!(StatusCode::TOOMANYREQUESTS.as_u16(), 429);
+        assert_eq!(StatusCode::from_u16(599).unwrap().reason_phrase(), "");
+    }
+
+    #[test]
+    // prueba de códigos numéricos arbitrarios
+    fn test_from_u16() {
+        assert_eq!(StatusCode::from_u16(200), Some(StatusCode::OK));
+        assert_eq!(StatusCode::try_from(418).unwrap().as_u16(), 418);
+        assert_eq!(StatusCode::from_u16(100).unwrap().as_u16(), 100);
+        assert_eq!(StatusCode::from_u16(599).unwrap().as_u16(), 599);
+        assert!(StatusCode::from_u16(99).is_none());
+        assert!(StatusCode::from_u16(600).is_none());
+        assert!(StatusCode::try_from(700).is_err());
+    }
+
+    #[test]
+    // prueba de clasificación por familia
+    fn test_classification() {
+        assert!(StatusCode::EARLYHINTS.is_informational());
+        assert!(StatusCode::NOCONTENT.is_success());
+        assert!(StatusCode::NOTMODIFIED.is_redirection());
+        assert!(StatusCode::UNPROCESSABLE.is_client_error());
+        assert!(!StatusCode::UNPROCESSABLE.is_server_error());
+        assert!(StatusCode::UNAVAILABLE.is_server_error());
+        assert!(StatusCode::from_u16(599).unwrap().is_server_error());
+    }
 }
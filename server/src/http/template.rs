@@ -0,0 +1,860 @@
+use super::utils::html_escape;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+// Máximo de includes/extends anidados; más allá se asume un ciclo
+const MAX_DEPTH: usize = 16;
+
+/// Valor que puede usarse dentro de una plantilla
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Value {
+    #[default]
+    Null,
+    Bool(bool),
+    Int(i64),
+    Str(String),
+    List(Vec<Value>),
+    Map(HashMap<String, Value>),
+}
+
+impl Value {
+    /// Indica si el valor cuenta como verdadero en un `if`: no es nulo, falso, cero ni vacío
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Bool(b) => *b,
+            Value::Int(n) => *n != 0,
+            Value::Str(s) => !s.is_empty(),
+            Value::List(list) => !list.is_empty(),
+            Value::Map(map) => !map.is_empty(),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null | Value::Map(_) => Ok(()),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Str(s) => write!(f, "{}", s),
+            Value::List(list) => {
+                for (i, item) in list.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Str(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Str(s)
+    }
+}
+
+impl From<&String> for Value {
+    fn from(s: &String) -> Self {
+        Value::Str(s.clone())
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(n: i64) -> Self {
+        Value::Int(n)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(n: i32) -> Self {
+        Value::Int(n as i64)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(n: u32) -> Self {
+        Value::Int(n as i64)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(n: usize) -> Self {
+        Value::Int(n as i64)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(opt: Option<T>) -> Self {
+        opt.map_or(Value::Null, Into::into)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(list: Vec<T>) -> Self {
+        Value::List(list.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Value>> From<HashMap<String, T>> for Value {
+    fn from(map: HashMap<String, T>) -> Self {
+        Value::Map(map.into_iter().map(|(k, v)| (k, v.into())).collect())
+    }
+}
+
+impl From<Context> for Value {
+    fn from(ctx: Context) -> Self {
+        Value::Map(ctx.vars)
+    }
+}
+
+/// Variables disponibles al renderizar una plantilla
+#[derive(Debug, Clone, Default)]
+pub struct Context {
+    vars: HashMap<String, Value>,
+}
+
+impl Context {
+    /// Crea un contexto vacío
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Define o reemplaza una variable
+    pub fn insert<V: Into<Value>>(&mut self, key: &str, value: V) {
+        self.vars.insert(key.to_string(), value.into());
+    }
+
+    /// Igual que `insert`, para encadenar
+    pub fn with<V: Into<Value>>(mut self, key: &str, value: V) -> Self {
+        self.insert(key, value);
+        self
+    }
+
+    /// Valor de una variable
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.vars.get(key)
+    }
+}
+
+/// Error al leer o interpretar una plantilla
+#[derive(Debug)]
+pub struct TemplateError {
+    pub template: String,
+    pub message: String,
+}
+
+impl TemplateError {
+    fn new(template: &str, message: String) -> Self {
+        Self {
+            template: template.to_string(),
+            message,
+        }
+    }
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "template '{}': {}", self.template, self.message)
+    }
+}
+
+impl Error for TemplateError {}
+
+// Trozos en que se divide el código de una plantilla; `tokenize` los devuelve junto a su línea
+enum Token {
+    Text(String),
+    Var(String),
+    Tag(String),
+}
+
+// Separa el texto de las etiquetas `{{ }}`, `{% %}` y `{# #}`; los comentarios se descartan
+fn tokenize(name: &str, src: &str) -> Result<Vec<(Token, usize)>, TemplateError> {
+    let mut tokens = Vec::new();
+    let mut rest = src;
+    let mut line = 1;
+    while let Some(start) = rest.find('{') {
+        let close = match rest[start..].chars().nth(1) {
+            Some('{') => "}}",
+            Some('%') => "%}",
+            Some('#') => "#}",
+            _ => {
+                // Una llave suelta es texto
+                let text = &rest[..start + 1];
+                push_text(&mut tokens, text, line);
+                line += text.matches('\n').count();
+                rest = &rest[start + 1..];
+                continue;
+            }
+        };
+        let text = &rest[..start];
+        push_text(&mut tokens, text, line);
+        line += text.matches('\n').count();
+
+        let body_start = start + 2;
+        let end = rest[body_start..].find(close).ok_or_else(|| {
+            TemplateError::new(
+                name,
+                format!("line {}: unclosed '{}'", line, &rest[start..body_start]),
+            )
+        })?;
+        let body = &rest[body_start..body_start + end];
+        match close {
+            "}}" => tokens.push((Token::Var(body.trim().to_string()), line)),
+            "%}" => tokens.push((Token::Tag(body.trim().to_string()), line)),
+            _ => {}
+        }
+        line += body.matches('\n').count();
+        rest = &rest[body_start + end + 2..];
+    }
+    push_text(&mut tokens, rest, line);
+    Ok(tokens)
+}
+
+// Agrega texto, uniéndolo con el anterior si también era texto
+fn push_text(tokens: &mut Vec<(Token, usize)>, text: &str, line: usize) {
+    if text.is_empty() {
+        return;
+    }
+    if let Some((Token::Text(prev), _)) = tokens.last_mut() {
+        prev.push_str(text);
+    } else {
+        tokens.push((Token::Text(text.to_string()), line));
+    }
+}
+
+// Condición de un `if`: una variable, opcionalmente negada con `not`
+#[derive(Debug, Clone)]
+struct Cond {
+    negate: bool,
+    path: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    /// Variable y si se escribe sin escapar (`| raw`)
+    Var(Vec<String>, bool),
+    /// Ramas `if`/`elif` y el contenido del `else`
+    If(Vec<(Cond, Vec<Node>)>, Vec<Node>),
+    /// Variable del bucle, lista recorrida, cuerpo y contenido del `else` si la lista está vacía
+    For(String, Vec<String>, Vec<Node>, Vec<Node>),
+    Include(String),
+    Block(String, Vec<Node>),
+}
+
+// Etiqueta que cierra un bloque: palabra clave, resto y línea
+type EndTag = (String, String, usize);
+
+struct Parser<'a> {
+    name: &'a str,
+    tokens: std::vec::IntoIter<(Token, usize)>,
+    extends: Option<String>,
+    // Se vio algo distinto de espacios antes del `extends`
+    started: bool,
+}
+
+impl Parser<'_> {
+    fn err(&self, line: usize, message: &str) -> TemplateError {
+        TemplateError::new(self.name, format!("line {}: {}", line, message))
+    }
+
+    // Lee nodos hasta una de las etiquetas `ends`; devuelve además la etiqueta que cerró
+    // el bloque o None si se terminó el código
+    fn nodes(&mut self, ends: &[&str]) -> Result<(Vec<Node>, Option<EndTag>), TemplateError> {
+        let mut nodes = Vec::new();
+        while let Some((token, line)) = self.tokens.next() {
+            let tag = match token {
+                Token::Text(text) => {
+                    self.started |= !text.trim().is_empty();
+                    nodes.push(Node::Text(text));
+                    continue;
+                }
+                Token::Var(expr) => {
+                    self.started = true;
+                    nodes.push(self.var(&expr, line)?);
+                    continue;
+                }
+                Token::Tag(tag) => tag,
+            };
+            let (keyword, rest) = match tag.split_once(char::is_whitespace) {
+                Some((keyword, rest)) => (keyword.to_string(), rest.trim().to_string()),
+                None => (tag.clone(), String::new()),
+            };
+            if ends.contains(&keyword.as_str()) {
+                return Ok((nodes, Some((keyword, rest, line))));
+            }
+            if keyword == "extends" {
+                if self.started || !ends.is_empty() || self.extends.is_some() {
+                    return Err(self.err(line, "'extends' must be the first tag"));
+                }
+                self.extends = Some(self.literal(&rest, line)?);
+                continue;
+            }
+            self.started = true;
+            let node = match keyword.as_str() {
+                "if" => self.if_node(&rest, line)?,
+                "for" => self.for_node(&rest, line)?,
+                "include" => Node::Include(self.literal(&rest, line)?),
+                "block" => {
+                    if !is_ident(&rest) {
+                        return Err(self.err(line, "invalid block name"));
+                    }
+                    let (body, _) = self.closed(&["endblock"], line)?;
+                    Node::Block(rest, body)
+                }
+                "elif" | "else" | "endif" | "endfor" | "endblock" => {
+                    return Err(self.err(line, &format!("unexpected '{}'", keyword)))
+                }
+                _ => return Err(self.err(line, &format!("unknown tag '{}'", keyword))),
+            };
+            nodes.push(node);
+        }
+        Ok((nodes, None))
+    }
+
+    // Como `nodes`, pero exige que aparezca alguna de las etiquetas de cierre
+    fn closed(&mut self, ends: &[&str], line: usize) -> Result<(Vec<Node>, EndTag), TemplateError> {
+        match self.nodes(ends)? {
+            (nodes, Some(end)) => Ok((nodes, end)),
+            (_, None) => {
+                Err(self.err(line, &format!("missing '{{% {} %}}'", ends[ends.len() - 1])))
+            }
+        }
+    }
+
+    fn if_node(&mut self, cond: &str, line: usize) -> Result<Node, TemplateError> {
+        let mut branches = Vec::new();
+        let mut cond = self.cond(cond, line)?;
+        loop {
+            let (body, (keyword, rest, end_line)) =
+                self.closed(&["elif", "else", "endif"], line)?;
+            branches.push((cond, body));
+            match keyword.as_str() {
+                "elif" => cond = self.cond(&rest, end_line)?,
+                "else" => {
+                    let (otherwise, _) = self.closed(&["endif"], line)?;
+                    return Ok(Node::If(branches, otherwise));
+                }
+                _ => return Ok(Node::If(branches, Vec::new())),
+            }
+        }
+    }
+
+    fn for_node(&mut self, spec: &str, line: usize) -> Result<Node, TemplateError> {
+        let parts: Vec<&str> = spec.split_whitespace().collect();
+        let (var, list) = match parts[..] {
+            [var, "in", list] if is_ident(var) => (var.to_string(), self.path(list, line)?),
+            _ => return Err(self.err(line, "expected '{% for <name> in <list> %}'")),
+        };
+        let (body, (keyword, _, _)) = self.closed(&["else", "endfor"], line)?;
+        let otherwise = match keyword.as_str() {
+            "else" => self.closed(&["endfor"], line)?.0,
+            _ => Vec::new(),
+        };
+        Ok(Node::For(var, list, body, otherwise))
+    }
+
+    fn var(&self, expr: &str, line: usize) -> Result<Node, TemplateError> {
+        let (path, raw) = match expr.split_once('|') {
+            Some((path, filter)) if filter.trim() == "raw" => (path, true),
+            Some((_, filter)) => {
+                return Err(self.err(line, &format!("unknown filter '{}'", filter.trim())))
+            }
+            None => (expr, false),
+        };
+        Ok(Node::Var(self.path(path.trim(), line)?, raw))
+    }
+
+    fn cond(&self, cond: &str, line: usize) -> Result<Cond, TemplateError> {
+        let (negate, path) = match cond.strip_prefix("not ") {
+            Some(path) => (true, path.trim()),
+            None => (false, cond),
+        };
+        Ok(Cond {
+            negate,
+            path: self.path(path, line)?,
+        })
+    }
+
+    // Ruta de una variable con acceso a campos e índices: `user.name`, `items.0`
+    fn path(&self, path: &str, line: usize) -> Result<Vec<String>, TemplateError> {
+        let parts: Vec<String> = path.split('.').map(str::to_string).collect();
+        let valid = is_ident(&parts[0])
+            && parts[1..]
+                .iter()
+                .all(|p| is_ident(p) || (!p.is_empty() && p.bytes().all(|b| b.is_ascii_digit())));
+        if !valid {
+            return Err(self.err(line, &format!("invalid variable '{}'", path)));
+        }
+        Ok(parts)
+    }
+
+    // Nombre de plantilla entre comillas
+    fn literal(&self, s: &str, line: usize) -> Result<String, TemplateError> {
+        let quoted = s.len() >= 2
+            && ((s.starts_with('"') && s.ends_with('"'))
+                || (s.starts_with('\'') && s.ends_with('\'')));
+        if !quoted {
+            return Err(self.err(line, "expected a quoted template name"));
+        }
+        Ok(s[1..s.len() - 1].to_string())
+    }
+}
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// Interpreta una plantilla y resuelve sus includes y su layout con `load`
+fn compile(
+    name: &str,
+    src: &str,
+    load: &mut dyn FnMut(&str) -> Result<String, TemplateError>,
+    depth: usize,
+) -> Result<Vec<Node>, TemplateError> {
+    if depth > MAX_DEPTH {
+        return Err(TemplateError::new(
+            name,
+            "too many nested includes".to_string(),
+        ));
+    }
+    let mut parser = Parser {
+        name,
+        tokens: tokenize(name, src)?.into_iter(),
+        extends: None,
+        started: false,
+    };
+    let (nodes, _) = parser.nodes(&[])?;
+    let nodes = resolve_includes(nodes, load, depth)?;
+
+    // Con un layout, el resultado es el padre con los bloques que la hija redefine
+    match parser.extends {
+        Some(parent) => {
+            let mut blocks = HashMap::new();
+            collect_blocks(nodes, &mut blocks);
+            let src = load(&parent)?;
+            let layout = compile(&parent, &src, load, depth + 1)?;
+            Ok(replace_blocks(layout, &blocks))
+        }
+        None => Ok(nodes),
+    }
+}
+
+fn resolve_includes(
+    nodes: Vec<Node>,
+    load: &mut dyn FnMut(&str) -> Result<String, TemplateError>,
+    depth: usize,
+) -> Result<Vec<Node>, TemplateError> {
+    let mut out = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        match node {
+            Node::Include(name) => {
+                let src = load(&name)?;
+                out.extend(compile(&name, &src, load, depth + 1)?);
+            }
+            Node::If(branches, otherwise) => {
+                let mut resolved = Vec::with_capacity(branches.len());
+                for (cond, body) in branches {
+                    resolved.push((cond, resolve_includes(body, load, depth)?));
+                }
+                out.push(Node::If(
+                    resolved,
+                    resolve_includes(otherwise, load, depth)?,
+                ));
+            }
+            Node::For(var, list, body, otherwise) => out.push(Node::For(
+                var,
+                list,
+                resolve_includes(body, load, depth)?,
+                resolve_includes(otherwise, load, depth)?,
+            )),
+            Node::Block(name, body) => {
+                out.push(Node::Block(name, resolve_includes(body, load, depth)?))
+            }
+            node => out.push(node),
+        }
+    }
+    Ok(out)
+}
+
+fn collect_blocks(nodes: Vec<Node>, blocks: &mut HashMap<String, Vec<Node>>) {
+    for node in nodes {
+        if let Node::Block(name, body) = node {
+            collect_blocks(body.clone(), blocks);
+            blocks.insert(name, body);
+        }
+    }
+}
+
+fn replace_blocks(nodes: Vec<Node>, blocks: &HashMap<String, Vec<Node>>) -> Vec<Node> {
+    let replace = |nodes| replace_blocks(nodes, blocks);
+    nodes
+        .into_iter()
+        .map(|node| match node {
+            Node::Block(name, body) => {
+                let body = blocks.get(&name).cloned().unwrap_or_else(|| replace(body));
+                Node::Block(name, body)
+            }
+            Node::If(branches, otherwise) => Node::If(
+                branches.into_iter().map(|(c, b)| (c, replace(b))).collect(),
+                replace(otherwise),
+            ),
+            Node::For(var, list, body, otherwise) => {
+                Node::For(var, list, replace(body), replace(otherwise))
+            }
+            node => node,
+        })
+        .collect()
+}
+
+// Variables visibles durante el renderizado: las del contexto y las de los bucles
+struct Scope<'a> {
+    ctx: &'a Context,
+    frames: Vec<HashMap<String, Value>>,
+}
+
+impl Scope<'_> {
+    fn lookup(&self, path: &[String]) -> Option<&Value> {
+        let mut value = self
+            .frames
+            .iter()
+            .rev()
+            .find_map(|frame| frame.get(&path[0]))
+            .or_else(|| self.ctx.get(&path[0]))?;
+        for key in &path[1..] {
+            value = match value {
+                Value::Map(map) => map.get(key)?,
+                Value::List(list) => list.get(key.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+
+    fn render(&mut self, nodes: &[Node], out: &mut String) {
+        for node in nodes {
+            match node {
+                Node::Text(text) => out.push_str(text),
+                Node::Var(path, raw) => {
+                    if let Some(value) = self.lookup(path) {
+                        let text = value.to_string();
+                        out.push_str(&if *raw { text } else { html_escape(&text) });
+                    }
+                }
+                Node::If(branches, otherwise) => {
+                    let body = branches
+                        .iter()
+                        .find(|(cond, _)| {
+                            self.lookup(&cond.path).is_some_and(Value::is_truthy) != cond.negate
+                        })
+                        .map_or(otherwise, |(_, body)| body);
+                    self.render(body, out);
+                }
+                Node::For(var, list, body, otherwise) => {
+                    let items = match self.lookup(list) {
+                        Some(Value::List(items)) if !items.is_empty() => items.clone(),
+                        _ => {
+                            self.render(otherwise, out);
+                            continue;
+                        }
+                    };
+                    let len = items.len();
+                    for (i, item) in items.into_iter().enumerate() {
+                        let info = Context::new()
+                            .with("index", i + 1)
+                            .with("index0", i)
+                            .with("first", i == 0)
+                            .with("last", i + 1 == len)
+                            .with("length", len);
+                        let mut frame = HashMap::new();
+                        frame.insert("loop".to_string(), info.into());
+                        frame.insert(var.clone(), item);
+                        self.frames.push(frame);
+                        self.render(body, out);
+                        self.frames.pop();
+                    }
+                }
+                Node::Block(_, body) => self.render(body, out),
+                // Los includes se resuelven al compilar
+                Node::Include(_) => {}
+            }
+        }
+    }
+}
+
+/// Plantilla HTML compilada. Admite variables escapadas (`{{ user.name }}`, o sin
+/// escapar con `{{ html | raw }}`), `{% if %}`/`{% elif %}`/`{% else %}`, bucles
+/// `{% for x in lista %}` con `loop.index`, `loop.first` y `loop.last`,
+/// `{% include "parcial.html" %}`, layouts con `{% extends "base.html" %}` y
+/// `{% block nombre %}`, y comentarios `{# ... #}`
+#[derive(Debug, Clone)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    /// Compila una plantilla desde un string; no puede usar `include` ni `extends`
+    pub fn parse(src: &str) -> Result<Self, TemplateError> {
+        let mut load = |name: &str| -> Result<String, TemplateError> {
+            Err(TemplateError::new(
+                name,
+                "includes need a template directory".to_string(),
+            ))
+        };
+        let nodes = compile("<inline>", src, &mut load, 0)?;
+        Ok(Self { nodes })
+    }
+
+    /// Genera el texto de la plantilla con las variables de `ctx`.
+    /// Las variables inexistentes se escriben vacías y cuentan como falsas
+    pub fn render(&self, ctx: &Context) -> String {
+        let mut out = String::new();
+        let mut scope = Scope {
+            ctx,
+            frames: Vec::new(),
+        };
+        scope.render(&self.nodes, &mut out);
+        out
+    }
+}
+
+// Plantilla compilada junto con los archivos de los que depende y su fecha de modificación
+struct Compiled {
+    template: Arc<Template>,
+    deps: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+/// Plantillas cargadas desde un directorio y guardadas compiladas en memoria.
+/// Se vuelven a compilar si cambia la plantilla o alguno de sus includes o layouts
+pub struct Templates {
+    dir: PathBuf,
+    cache: Mutex<HashMap<String, Compiled>>,
+}
+
+impl Templates {
+    /// Crea un cargador de plantillas para el directorio `dir`
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Devuelve la plantilla `name`, relativa al directorio, compilándola si hace falta
+    pub fn get(&self, name: &str) -> Result<Arc<Template>, TemplateError> {
+        if let Some(compiled) = self.cache.lock().unwrap().get(name) {
+            let fresh = compiled
+                .deps
+                .iter()
+                .all(|(path, mtime)| modified(path) == *mtime);
+            if fresh {
+                return Ok(compiled.template.clone());
+            }
+        }
+
+        // La compilación se hace sin tener tomado el lock
+        let mut deps = Vec::new();
+        let mut load = |name: &str| self.load(name, &mut deps);
+        let src = load(name)?;
+        let nodes = compile(name, &src, &mut load, 0)?;
+        let template = Arc::new(Template { nodes });
+        let compiled = Compiled {
+            template: template.clone(),
+            deps,
+        };
+        self.cache
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), compiled);
+        Ok(template)
+    }
+
+    /// Renderiza la plantilla `name` con las variables de `ctx`
+    pub fn render(&self, name: &str, ctx: &Context) -> Result<String, TemplateError> {
+        Ok(self.get(name)?.render(ctx))
+    }
+
+    /// Descarta las plantillas compiladas
+    pub fn clear(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+
+    // Lee una plantilla del directorio, sin permitir salir de él
+    fn load(
+        &self,
+        name: &str,
+        deps: &mut Vec<(PathBuf, Option<SystemTime>)>,
+    ) -> Result<String, TemplateError> {
+        let relative = Path::new(name);
+        if !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Err(TemplateError::new(
+                name,
+                "invalid template name".to_string(),
+            ));
+        }
+        let path = self.dir.join(relative);
+        let mtime = modified(&path);
+        let src = fs::read_to_string(&path)
+            .map_err(|e| TemplateError::new(name, format!("could not read file ({})", e)))?;
+        deps.push((path, mtime));
+        Ok(src)
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::time::Duration;
+
+    fn render(src: &str, ctx: &Context) -> String {
+        Template::parse(src).unwrap().render(ctx)
+    }
+
+    #[test]
+    // prueba de variables, escape HTML y acceso a campos
+    fn test_variables() {
+        let mut user = HashMap::new();
+        user.insert("name".to_string(), "Ana <admin>");
+        let ctx = Context::new()
+            .with("user", user)
+            .with("count", 3)
+            .with("tags", vec!["a", "b"])
+            .with("html", "<b>hola</b>");
+
+        assert_eq!(
+            render("Hola {{ user.name }}!", &ctx),
+            "Hola Ana &lt;admin&gt;!"
+        );
+        assert_eq!(
+            render("{{count}} {{ tags }} {{ tags.1 }}", &ctx),
+            "3 a, b b"
+        );
+        assert_eq!(render("{{ html | raw }}", &ctx), "<b>hola</b>");
+        assert_eq!(render("[{{ missing }}] {{ user.x.y }}", &ctx), "[] ");
+        assert_eq!(render("a {b} {# nota #}c", &ctx), "a {b} c");
+    }
+
+    #[test]
+    // prueba de condicionales y bucles
+    fn test_if_and_for() {
+        let ctx = Context::new()
+            .with("error", "Clave incorrecta")
+            .with("items", vec!["uno", "dos", "tres"])
+            .with("empty", Vec::<String>::new());
+
+        let src = "{% if error %}<p>{{ error }}</p>{% else %}ok{% endif %}";
+        assert_eq!(render(src, &ctx), "<p>Clave incorrecta</p>");
+        assert_eq!(render(src, &Context::new()), "ok");
+        assert_eq!(render("{% if not error %}si{% endif %}", &ctx), "");
+        assert_eq!(
+            render(
+                "{% if a %}a{% elif b %}b{% else %}c{% endif %}",
+                &ctx.clone().with("b", true)
+            ),
+            "b"
+        );
+
+        let src = "{% for x in items %}{{ loop.index }}.{{ x }}{% if not loop.last %},{% endif %}{% endfor %}";
+        assert_eq!(render(src, &ctx), "1.uno,2.dos,3.tres");
+        let src = "{% for x in empty %}{{ x }}{% else %}nada{% endfor %}";
+        assert_eq!(render(src, &ctx), "nada");
+    }
+
+    #[test]
+    // prueba de errores de sintaxis
+    fn test_parse_errors() {
+        let err = |src: &str| Template::parse(src).unwrap_err().message;
+        assert_eq!(err("a\n{% if x %}b"), "line 2: missing '{% endif %}'");
+        assert_eq!(err("{{ x"), "line 1: unclosed '{{'");
+        assert_eq!(err("{% endfor %}"), "line 1: unexpected 'endfor'");
+        assert_eq!(err("{% foo %}"), "line 1: unknown tag 'foo'");
+        assert_eq!(err("{{ x | upper }}"), "line 1: unknown filter 'upper'");
+        assert_eq!(err("{{ a b }}"), "line 1: invalid variable 'a b'");
+        assert_eq!(
+            err("x {% extends \"base.html\" %}"),
+            "line 1: 'extends' must be the first tag"
+        );
+        assert!(Template::parse("{% include \"x.html\" %}").is_err());
+    }
+
+    #[test]
+    // prueba de includes, layouts y recarga de plantillas modificadas
+    fn test_templates_dir() {
+        let dir = env::temp_dir().join(format!("templates-{}", std::process::id()));
+        fs::create_dir_all(dir.join("partials")).unwrap();
+        fs::write(
+            dir.join("base.html"),
+            "<title>{% block title %}Sitio{% endblock %}</title>\
+             {% include \"partials/nav.html\" %}<main>{% block content %}{% endblock %}</main>",
+        )
+        .unwrap();
+        fs::write(dir.join("partials/nav.html"), "<nav>{{ user }}</nav>").unwrap();
+        fs::write(
+            dir.join("page.html"),
+            "{% extends \"base.html\" %}\n{% block content %}Hola {{ user }}{% endblock %}",
+        )
+        .unwrap();
+
+        let templates = Templates::new(&dir);
+        let ctx = Context::new().with("user", "ana");
+        assert_eq!(
+            templates.render("page.html", &ctx).unwrap(),
+            "<title>Sitio</title><nav>ana</nav><main>Hola ana</main>"
+        );
+        let first = templates.get("page.html").unwrap();
+        assert!(Arc::ptr_eq(&first, &templates.get("page.html").unwrap()));
+
+        // un cambio en un include invalida la plantilla compilada
+        let nav = dir.join("partials/nav.html");
+        fs::write(&nav, "<nav>[{{ user }}]</nav>").unwrap();
+        let file = fs::File::options().write(true).open(&nav).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(
+            templates.render("page.html", &ctx).unwrap(),
+            "<title>Sitio</title><nav>[ana]</nav><main>Hola ana</main>"
+        );
+
+        // ciclos, archivos inexistentes y rutas fuera del directorio
+        fs::write(dir.join("loop.html"), "{% include \"loop.html\" %}").unwrap();
+        assert!(templates.get("loop.html").is_err());
+        assert!(templates.get("missing.html").is_err());
+        assert_eq!(
+            templates.get("../etc/passwd").unwrap_err().message,
+            "invalid template name"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
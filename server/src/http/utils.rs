@@ -11,6 +11,156 @@ pub fn parse_url_param(input: &str) -> Result<HashMap<&str, &str>, &'static str>
     Ok(ans)
 }
 
+// Decodifica las secuencias `%XX` de un texto; falla si alguna es inválida
+// o si el resultado no es UTF-8
+pub fn percent_decode(input: &str) -> Option<String> {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = input.get(i + 1..i + 3)?;
+            if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return None;
+            }
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+// Compara un texto con un patrón donde cada '*' coincide con cualquier secuencia
+// (incluida '/'); devuelve lo capturado por cada '*' en orden
+pub fn wildcard_match<'a>(pattern: &str, text: &'a str) -> Option<Vec<&'a str>> {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let mut rest = text.strip_prefix(parts[0])?;
+    if parts.len() == 1 {
+        return rest.is_empty().then(Vec::new);
+    }
+
+    let mut captures = Vec::with_capacity(parts.len() - 1);
+    for (i, part) in parts.iter().enumerate().skip(1) {
+        if i == parts.len() - 1 {
+            // El último literal debe coincidir con el final del texto
+            captures.push(rest.strip_suffix(part)?);
+        } else {
+            let idx = rest.find(part)?;
+            captures.push(&rest[..idx]);
+            rest = &rest[idx + part.len()..];
+        }
+    }
+    Some(captures)
+}
+
+// Reemplaza `$1`..`$9` en `template` por las capturas correspondientes
+pub fn substitute(template: &str, captures: &[&str]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        let index = match (c, chars.peek().and_then(|d| d.to_digit(10))) {
+            ('$', Some(d)) if d > 0 => d as usize,
+            _ => {
+                out.push(c);
+                continue;
+            }
+        };
+        chars.next();
+        out.push_str(captures.get(index - 1).copied().unwrap_or(""));
+    }
+    out
+}
+
+// Codifica como `%XX` todo lo que no sea un carácter no reservado (RFC 3986)
+pub fn percent_encode(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for b in input.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(b as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+// Escapa los caracteres especiales de HTML
+pub fn html_escape(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// Escapa un texto para usarlo dentro de un string JSON
+pub fn json_escape(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// Elige la codificación preferida por el cliente según `Accept-Encoding` (RFC 9110 12.5.3).
+// `supported` va en orden de preferencia del servidor, que desempata a igual q
+pub fn negotiate_encoding<'a>(accept: &str, supported: &[&'a str]) -> Option<&'a str> {
+    let mut prefs: Vec<(String, f32)> = Vec::new();
+    for item in accept.split(',') {
+        let mut parts = item.split(';');
+        let name = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+        if name.is_empty() {
+            continue;
+        }
+        let q = parts
+            .filter_map(|p| p.trim().strip_prefix("q="))
+            .find_map(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+        // x-gzip es un alias histórico de gzip
+        let name = if name == "x-gzip" {
+            "gzip".into()
+        } else {
+            name
+        };
+        prefs.push((name, q));
+    }
+
+    let q_of = |name: &str| {
+        prefs
+            .iter()
+            .find(|(n, _)| n == name)
+            .or_else(|| prefs.iter().find(|(n, _)| n == "*"))
+            .map_or(0.0, |(_, q)| *q)
+    };
+    let mut best: Option<(&str, f32)> = None;
+    for &enc in supported {
+        let q = q_of(enc);
+        if q > 0.0 && best.is_none_or(|(_, bq)| q > bq) {
+            best = Some((enc, q));
+        }
+    }
+    best.map(|(enc, _)| enc)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -47,4 +197,73 @@ mod tests {
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), "Invalid URL param");
     }
+
+    #[test]
+    // prueba de decodificación de secuencias %XX
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("a%20b").as_deref(), Some("a b"));
+        assert_eq!(percent_decode("%2e%2E").as_deref(), Some(".."));
+        assert_eq!(percent_decode("plain").as_deref(), Some("plain"));
+        assert_eq!(percent_decode("bad%2"), None);
+        assert_eq!(percent_decode("bad%zz"), None);
+        assert_eq!(percent_decode("%+f"), None);
+        assert_eq!(percent_decode("%ff"), None);
+    }
+
+    #[test]
+    // prueba de codificación y escapes para URL, HTML y JSON
+    fn test_escapes() {
+        assert_eq!(percent_encode("a b/ñ.txt"), "a%20b%2F%C3%B1.txt");
+        assert_eq!(
+            html_escape("<a href=\"x\">&'</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&#39;&lt;/a&gt;"
+        );
+        assert_eq!(
+            json_escape("say \"hi\"\\\n\u{1}"),
+            "say \\\"hi\\\"\\\\\\n\\u0001"
+        );
+    }
+
+    #[test]
+    // prueba de patrones con comodines y sustitución de capturas
+    fn test_wildcard_match() {
+        assert_eq!(wildcard_match("/old", "/old"), Some(vec![]));
+        assert_eq!(wildcard_match("/old", "/older"), None);
+        assert_eq!(
+            wildcard_match("/blog/*", "/blog/2024/post"),
+            Some(vec!["2024/post"])
+        );
+        assert_eq!(
+            wildcard_match("/u/*/posts/*.html", "/u/ana/posts/hola.html"),
+            Some(vec!["ana", "hola"])
+        );
+        assert_eq!(wildcard_match("*.css", "/a/b.css"), Some(vec!["/a/b"]));
+        assert_eq!(wildcard_match("/blog/*", "/about"), None);
+
+        assert_eq!(substitute("/posts/$1?x=$2", &["a", "b"]), "/posts/a?x=b");
+        assert_eq!(substitute("/cost/$0/$3/$", &["a"]), "/cost/$0//$");
+    }
+
+    #[test]
+    // prueba de negociación de Accept-Encoding con valores q
+    fn test_negotiate_encoding() {
+        let supported = ["gzip", "deflate"];
+        assert_eq!(
+            negotiate_encoding("gzip, deflate, br", &supported),
+            Some("gzip")
+        );
+        assert_eq!(negotiate_encoding("deflate", &supported), Some("deflate"));
+        assert_eq!(
+            negotiate_encoding("gzip;q=0.5, deflate;q=0.8", &supported),
+            Some("deflate")
+        );
+        assert_eq!(
+            negotiate_encoding("gzip;q=0, *", &supported),
+            Some("deflate")
+        );
+        assert_eq!(negotiate_encoding("*;q=0", &supported), None);
+        assert_eq!(negotiate_encoding("br, identity", &supported), None);
+        assert_eq!(negotiate_encoding("X-GZIP", &supported), Some("gzip"));
+        assert_eq!(negotiate_encoding("", &supported), None);
+    }
 }
@@ -0,0 +1,2 @@
+// Servidor HTTP: el binario (main.rs) usa este módulo como biblioteca
+pub mod http;
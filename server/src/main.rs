@@ -1,7 +1,11 @@
 // Dependencias
-mod http;
-use http::{parse_url_param, Method, Response, Server};
+use server::http::{
+    parse_url_param, Compression, Context, HttpError, Response, Server, StatusCode,
+    Templates, TrailingSlash,
+};
 use std::env;
+use std::process;
+use std::sync::Arc;
 
 fn main() {
     // Obtenemos los arguentos de la línea de comandos
@@ -12,55 +16,51 @@ fn main() {
     let thread_qty: usize = args[2].parse().unwrap();
 
     let mut server = Server::new("127.0.0.1", &args[1]);
+    server.trailing_slash(TrailingSlash::Redirect);
+    // Comprime HTML, CSS y JS según el Accept-Encoding del cliente
+    server.middleware(Compression::new());
+    // El HTML se revalida siempre con el ETag
+    server.cache_control_mime("text/html", "no-cache");
+    // Mantiene en memoria hasta 16 MiB de archivos servidos con on_file
+    server.file_cache(16 * 1024 * 1024);
     server.on_file(r"/index.html", "./static/index.html");
-    server.on(r"/", |_| Response::redirect("/index.html"));
+    server.redirect(r"/", "/index.html", StatusCode::REDIRECT);
 
     // Simulamos una página de login
     server.on_file(r"/login", "./static/login.html");
 
+    // Página generada con una plantilla: /welcome?name=Ana
+    let templates = Arc::new(Templates::new("./templates"));
+    server.on(r"/welcome", move |req| -> Result<Response, HttpError> {
+        let query = req.path.parse_params()?;
+        let ctx = Context::new().with("name", query.get("name").copied());
+        let template = templates.get("welcome.html")?;
+        Ok(Response::render(&template, &ctx))
+    });
+
     // Simulamos el API de login
-    server.on(r"/api/login", |req| {
-        // Solo aceptamos solicitudes POST
-        if req.method != Method::POST {
-            return Response::internal_err("Only post requests are allowed");
-        }
-    
+    server.on(r"/api/login", |req| -> Result<Response, HttpError> {
         // Parsear los parámetros del cuerpo de la solicitud
-        let body = match parse_url_param(&req.body) {
-            Ok(b) => b,
-            Err(_) => return Response::internal_err("Couldn't parse body parameters"),
-        };
-    
-        // Obtener el valor del campo 'email'
-        let email = match body.get("email") {
-            Some(v) => v,
-            None => return Response::internal_err("Missing email"),
-        };
+        let body = parse_url_param(&req.body)?;
 
-        // Obtener el valor del campo 'password'
-        let password = match body.get("password") {
-            Some(v) => v,
-            None => return Response::internal_err("Missing password"),
-        };
+        // Obtener los valores de los campos 'email' y 'password'
+        let email = body
+            .get("email")
+            .ok_or_else(|| HttpError::bad_request("Missing email"))?;
+        let password = body
+            .get("password")
+            .ok_or_else(|| HttpError::bad_request("Missing password"))?;
 
         // Crear una respuesta con los valores de email y password
         let response = format!("Email: {}, Password: {}\n", email, password);
-        Response::ok(&response)
+        Ok(Response::ok(&response))
     });
-    
-    // Simulamos una API de pruebas
-    server.on(r"/api/tests", |req| {
-        // Parsear los parámetros de la URL
-        let mut query = match req.path.parse_params() {
-            Ok(q) => q,
-            Err(_) => return Response::internal_err("Couldn't parse query parameters"),
-        };
 
-        // Parsear los parámetros del cuerpo de la solicitud
-        let body = match parse_url_param(&req.body) {
-            Ok(b) => b,
-            Err(_) => return Response::internal_err("Couldn't parse body parameters"),
-        };
+    // Simulamos una API de pruebas
+    server.on(r"/api/tests", |req| -> Result<Response, HttpError> {
+        // Parsear los parámetros de la URL y del cuerpo de la solicitud
+        let mut query = req.path.parse_params()?;
+        let body = parse_url_param(&req.body)?;
 
         // Mezclar los parámetros de la URL y del cuerpo
         query.extend(body);
@@ -72,16 +72,21 @@ fn main() {
         }
 
         // Devolver la respuesta
-        Response::ok(&response)
+        Ok(Response::ok(&response))
     });
 
-    server.run(thread_qty)
+    server.print_routes(true);
+    server.check_files(true);
+    if let Err(e) = server.run(thread_qty) {
+        eprintln!("[Error] {}", e);
+        process::exit(1);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use http::{Headers, Method, Request, StatusCode};
+    use server::http::{Headers, Method, Request};
 
     #[test]
     fn test_login_handler() {
@@ -137,16 +142,14 @@ mod tests {
 
         // Test GET request
         let req = Request {
-            method: Method::GET,
-            path: "/login?email=test@example.com&password=123456".into(),
-            headers: headers,
-            body: String::new(),
+            headers,
+            ..Request::new(Method::GET, "/login?email=test@example.com&password=123456")
         };
         let response = login_handler(&req);
 
         assert_eq!(response.status, StatusCode::OK);
         assert_eq!(
-            String::from_utf8(response.body).unwrap(),
+            String::from_utf8(response.body.into_bytes().unwrap()).unwrap(),
             "Email: test@example.com, Password: 123456\n"
         );
 
@@ -158,16 +161,15 @@ mod tests {
         let headers = Headers::from(&vec);
         // Test POST request
         let req = Request {
-            method: Method::POST,
-            path: "/login".into(),
-            headers: headers,
+            headers,
             body: "email=post@example.com&password=654321".to_string(),
+            ..Request::new(Method::POST, "/login")
         };
         let response = login_handler(&req);
 
         assert_eq!(response.status, StatusCode::OK);
         assert_eq!(
-            String::from_utf8(response.body).unwrap(),
+            String::from_utf8(response.body.into_bytes().unwrap()).unwrap(),
             "Email: post@example.com, Password: 654321\n"
         );
 
@@ -179,16 +181,14 @@ mod tests {
         let headers = Headers::from(&vec);
         // Test invalid method
         let req = Request {
-            method: Method::PUT,
-            path: "/login".into(),
             headers,
-            body: String::new(),
+            ..Request::new(Method::PUT, "/login")
         };
         let response = login_handler(&req);
 
         assert_eq!(response.status, StatusCode::INTERNALERR);
         assert_eq!(
-            String::from_utf8(response.body).unwrap(),
+            String::from_utf8(response.body.into_bytes().unwrap()).unwrap(),
             "Only get or post requests are allowed"
         );
     }